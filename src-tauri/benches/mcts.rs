@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use chkrs::agent::{Agent, MctsConfig};
+use chkrs::agent::mcts_hash::MctsHashAgent;
+use chkrs::agent::mcts_tree::MctsTreeAgent;
+use chkrs::board::{fast, Checkers};
+
+
+const ITERATIONS: usize = 2_000;
+
+// a full search from the starting position with each agent, the same number of iterations each
+fn bench_agents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mcts");
+    group.sample_size(10);
+
+    let root = <fast::BoardState as Checkers>::default();
+    group.bench_function("hash", |b| b.iter(|| {
+        MctsHashAgent::new(MctsConfig { iterations: ITERATIONS, ..MctsConfig::default() }).get_best_move(root)
+    }));
+    group.bench_function("tree", |b| b.iter(|| {
+        MctsTreeAgent::new(MctsConfig { iterations: ITERATIONS, ..MctsConfig::default() }).get_best_move(root)
+    }));
+}
+
+// iterations per second of the hash agent, split between a growing number of threads
+fn bench_threads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mcts threads");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ITERATIONS as u64 * 4));
+
+    let root = <fast::BoardState as Checkers>::default();
+    // powers of two up to the number of cores, and all of the cores
+    let max_threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut thread_counts = (0..).map(|power| 1 << power).take_while(|threads| *threads < max_threads).collect::<Vec<usize>>();
+    thread_counts.push(max_threads);
+    for threads in thread_counts {
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, threads| b.iter(|| {
+            let config = MctsConfig { iterations: ITERATIONS * 4, threads: Some(*threads), ..MctsConfig::default() };
+            MctsHashAgent::new(config).get_best_move(root)
+        }));
+    }
+}
+
+criterion_group!(benches, bench_agents, bench_threads);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use chkrs::board::{fast, Checkers};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+
+// positions from a few random games, so the generators see openings, middlegames and king endings
+fn sample_positions() -> Vec<fast::BoardState> {
+    let mut random = SmallRng::seed_from_u64(14);
+    let mut positions = vec![];
+    for _ in 0..20 {
+        let mut state = <fast::BoardState as Checkers>::default();
+        for _ in 0..100 {
+            let moves = state.get_legal_moves();
+            let Some(mv) = moves.choose(&mut random) else { break };
+            positions.push(state);
+            state = state.make_move(*mv);
+        }
+    }
+    positions
+}
+
+fn bench_movegen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movegen");
+    group.sample_size(100).significance_level(0.05);
+    let positions = sample_positions();
+
+    group.bench_function("per piece", |b| b.iter(|| {
+        positions.iter().map(|state| state.get_legal_moves_per_piece().len()).sum::<usize>()
+    }));
+    group.bench_function("shifts", |b| b.iter(|| {
+        positions.iter().map(|state| state.get_legal_moves().len()).sum::<usize>()
+    }));
+}
+
+criterion_group!(benches, bench_movegen);
+criterion_main!(benches);
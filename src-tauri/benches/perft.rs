@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use chkrs::board::{fast, slow, Checkers};
+use chkrs::board::perft::{perft, perft_parallel};
+
+
+fn bench_perft(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+
+    group.bench_function("slow (depth 5)", |b| b.iter(|| {
+        perft(slow::BoardState::default(), 5)
+    }));
+    group.bench_function("fast (depth 5)", |b| b.iter(|| {
+        perft(<fast::BoardState as Checkers>::default(), 5)
+    }));
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    group.bench_function("fast (depth 7, 1 thread)", |b| b.iter(|| {
+        perft(<fast::BoardState as Checkers>::default(), 7)
+    }));
+    group.bench_function(format!("fast (depth 7, {} threads)", threads), |b| b.iter(|| {
+        perft_parallel(<fast::BoardState as Checkers>::default(), 7, threads)
+    }));
+}
+
+criterion_group!(benches, bench_perft);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use chkrs::board::{fast, slow, Checkers, Move};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+
+
+const MAX_PLIES: usize = 500;
+
+// random playouts like the ones `MctsHashAgent` does, once with heap allocated lists per ply and once without
+// the bitboard still has the allocating generator the trait used before `MoveList`, the array board does not
+fn rollout_vec(state: fast::BoardState, random: &mut SmallRng) -> usize {
+    let mut state = state;
+    for ply in 0..MAX_PLIES {
+        let moves = fast::BoardState::get_legal_moves(&state).into_iter().map(Move::from).collect::<Vec<_>>();
+        let Some(mv) = moves.choose(random) else { return ply };
+        state = Checkers::make_move(&state, *mv);
+    }
+    MAX_PLIES
+}
+
+fn rollout_move_list<C: Checkers>(state: C, random: &mut SmallRng) -> usize {
+    let mut state = state;
+    for ply in 0..MAX_PLIES {
+        let moves = state.get_legal_move_list();
+        let Some(mv) = moves.choose(random) else { return ply };
+        state = state.make_move(*mv);
+    }
+    MAX_PLIES
+}
+
+fn bench_rollouts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rollouts");
+    group.sample_size(50);
+
+    let mut random = SmallRng::seed_from_u64(15);
+    group.bench_function("slow (move list)", |b| b.iter(|| rollout_move_list(slow::BoardState::default(), &mut random)));
+    group.bench_function("fast (vec)", |b| b.iter(|| rollout_vec(<fast::BoardState as Checkers>::default(), &mut random)));
+    group.bench_function("fast (move list)", |b| b.iter(|| rollout_move_list(<fast::BoardState as Checkers>::default(), &mut random)));
+}
+
+criterion_group!(benches, bench_rollouts);
+criterion_main!(benches);
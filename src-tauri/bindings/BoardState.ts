@@ -2,4 +2,4 @@
 import type { Player } from "./Player";
 import type { Tile } from "./Tile";
 
-export type BoardState = { tiles: [[Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null]], turn: Player, };
\ No newline at end of file
+export type BoardState = { tiles: [[Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null], [Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null, Tile | null]], turn: Player, capturing: [number, number] | null, };
\ No newline at end of file
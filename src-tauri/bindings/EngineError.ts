@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameOutcome } from "./GameOutcome";
+import type { IllegalMove } from "./IllegalMove";
+
+export type EngineError = { "InvalidBoard": string } | { "IllegalMove": IllegalMove } | { "InvalidNotation": string } | { "GameOver": GameOutcome } | { "SearchFailed": string };
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DrawReason } from "./DrawReason";
+import type { Player } from "./Player";
+
+export type GameOutcome = { "Win": Player } | { "Draw": DrawReason } | "Ongoing";
\ No newline at end of file
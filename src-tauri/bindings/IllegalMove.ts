@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameOutcome } from "./GameOutcome";
+
+export type IllegalMove = { "GameOver": GameOutcome } | "NoPiece" | "WrongSide" | "IllegalDestination" | "CaptureAvailable";
\ No newline at end of file
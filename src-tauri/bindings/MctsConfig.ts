@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RolloutPolicy } from "./RolloutPolicy";
+
+export type MctsConfig = { iterations: number, exploration: number, rollout_policy: RolloutPolicy, max_rollout_plies: number, seed: number, time_budget_ms: number | null, threads: number | null, };
\ No newline at end of file
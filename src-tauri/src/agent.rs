@@ -1,8 +1,81 @@
+use std::time::Duration;
+use ts_rs::TS;
+use rollout::RolloutPolicy;
 use crate::board::{Checkers, Move};
 
+pub mod alphabeta;
+pub mod arena;
+pub mod book;
+pub mod evaluation;
 pub mod mcts_hash;
 pub mod mcts_tree;
+pub mod rollout;
 
 pub trait Agent<C: Checkers> {
+    // the root has to have a legal move, i.e. the game must not be over
     fn get_best_move(&mut self, root: C) -> Move;
+}
+
+// plays the first legal move, a deterministic opponent for tests
+#[cfg(test)]
+pub(crate) struct FirstMoveAgent;
+
+#[cfg(test)]
+impl<C: Checkers> Agent<C> for FirstMoveAgent {
+    fn get_best_move(&mut self, root: C) -> Move {
+        root.get_legal_moves()[0]
+    }
+}
+
+// the searchers the frontend can pick between
+#[derive(TS, Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub enum AgentKind {
+    #[default]
+    Mcts,
+    AlphaBeta,
+}
+
+// the parameters of the monte carlo agents, missing fields take their default value when deserialized
+#[derive(TS, Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+#[ts(export)]
+pub struct MctsConfig {
+    pub iterations: usize,
+    // the weight of the exploration term of UCT, `sqrt(2 ln N / n)`, next to the average reward between 0 and 1
+    pub exploration: f64,
+    pub rollout_policy: RolloutPolicy,
+    // rollouts that are still going after this many moves are adjudicated by the evaluator
+    pub max_rollout_plies: usize,
+    #[ts(type = "number")]
+    pub seed: u64,
+    // when set, the search runs for this many milliseconds instead of a fixed number of iterations
+    pub time_budget_ms: Option<u32>,
+    // how many threads the hash agent searches with, a single one when unset
+    // (`commands::get_best_move` fills it in with the available cores)
+    pub threads: Option<usize>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 20_000,
+            exploration: 0.7,
+            rollout_policy: RolloutPolicy::Uniform,
+            max_rollout_plies: 500,
+            seed: 6,
+            time_budget_ms: None,
+            threads: None,
+        }
+    }
+}
+
+impl MctsConfig {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.time_budget_ms.map(|ms| Duration::from_millis(ms as u64))
+    }
 }
\ No newline at end of file
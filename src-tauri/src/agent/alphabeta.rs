@@ -0,0 +1,372 @@
+// a classical searcher: negamax alpha-beta over complete turns, deepened iteratively until the depth or time limit
+// moves are ordered by the transposition table's best move, captures, killer moves and the history heuristic,
+// and the leaves are extended while captures are pending (which are mandatory, so there is no "standing pat")
+use std::time::{Duration, Instant};
+use crate::board::{Checkers, CompoundMove, Move};
+use super::Agent;
+use super::evaluation::{Evaluator, HandCrafted};
+
+
+const WIN_SCORE: i32 = 1_000_000;
+// scores beyond this are wins or losses, with the distance to the end subtracted
+const WIN_THRESHOLD: i32 = WIN_SCORE - 1_000;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Bound {
+    Exact,
+    // the score is at least this (the search failed high)
+    Lower,
+    // the score is at most this (no move reached alpha)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    // the index of the best move in the order `get_legal_compound_moves` lists them
+    best_move: u8,
+}
+
+// a fixed-size table indexed by zobrist hash, where deeper searches replace shallower ones
+struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    fn new(size: usize) -> Self {
+        Self { entries: vec![None; size.max(1)] }
+    }
+
+    fn get(&self, key: u64) -> Option<Entry> {
+        self.entries[key as usize % self.entries.len()].filter(|entry| entry.key == key)
+    }
+
+    fn insert(&mut self, entry: Entry) {
+        let index = entry.key as usize % self.entries.len();
+        let slot = &mut self.entries[index];
+        if slot.map_or(true, |existing| existing.key != entry.key || existing.depth <= entry.depth) {
+            *slot = Some(entry);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+// mate scores count the plies from the root, but an entry can be hit at any ply, so the table counts them from the
+// position the entry belongs to instead
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score > WIN_THRESHOLD {
+        score + ply as i32
+    } else if score < -WIN_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score > WIN_THRESHOLD {
+        score - ply as i32
+    } else if score < -WIN_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+// killer and history moves are identified by where they start and end
+type MoveKey = ((u8, u8), (u8, u8));
+
+fn move_key(mv: &CompoundMove) -> MoveKey {
+    (mv.from, mv.to())
+}
+
+fn square_index(coords: (u8, u8)) -> usize {
+    8 * coords.0 as usize + coords.1 as usize
+}
+
+pub struct AlphaBetaAgent<E = HandCrafted> {
+    pub evaluator: E,
+    // in complete turns
+    pub max_depth: usize,
+    pub time_limit: Option<Duration>,
+    table: TranspositionTable,
+    killers: Vec<[Option<MoveKey>; 2]>,
+    history: Vec<[u32; 64]>,
+    deadline: Option<Instant>,
+    aborted: bool,
+    // the index of the best move found at the root by the last completed iteration
+    root_move: usize,
+    pub nodes: usize,
+}
+
+impl Default for AlphaBetaAgent {
+    fn default() -> Self {
+        Self::new(8, Some(Duration::from_secs(2)), 1 << 20)
+    }
+}
+
+impl AlphaBetaAgent {
+    pub fn new(max_depth: usize, time_limit: Option<Duration>, table_size: usize) -> Self {
+        Self::with_evaluator(HandCrafted::default(), max_depth, time_limit, table_size)
+    }
+}
+
+impl<E: Evaluator> AlphaBetaAgent<E> {
+    pub fn with_evaluator(evaluator: E, max_depth: usize, time_limit: Option<Duration>, table_size: usize) -> Self {
+        Self {
+            evaluator,
+            max_depth,
+            time_limit,
+            table: TranspositionTable::new(table_size),
+            killers: vec![],
+            history: vec![[0; 64]; 64],
+            deadline: None,
+            aborted: false,
+            root_move: 0,
+            nodes: 0,
+        }
+    }
+
+    // the best complete turn along with its score, from the perspective of the side to move
+    pub fn search<C: Checkers>(&mut self, root: C) -> Option<(CompoundMove, i32)> {
+        self.deadline = self.time_limit.map(|time_limit| Instant::now() + time_limit);
+        self.aborted = false;
+        self.nodes = 0;
+        self.table.clear();
+        self.killers = vec![[None; 2]; self.max_depth + 1];
+        self.history.iter_mut().for_each(|row| row.fill(0));
+
+        let moves = root.get_legal_compound_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        let mut best = None;
+        for depth in 1..=self.max_depth {
+            let score = self.negamax(root, depth, 0, -WIN_SCORE, WIN_SCORE);
+            // the results of an interrupted iteration can not be trusted
+            if self.aborted {
+                break;
+            }
+
+            best = Some((moves[self.root_move].clone(), score));
+
+            // a forced win or loss will not change with more depth
+            if score.abs() > WIN_THRESHOLD {
+                break;
+            }
+        }
+
+        // even the first iteration ran out of time, so any move has to do
+        best.or_else(|| Some((moves[0].clone(), 0)))
+    }
+
+    fn is_out_of_time(&mut self) -> bool {
+        // checking the clock on every node would be wasteful
+        if self.nodes % 1024 == 0 && self.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    fn negamax<C: Checkers>(&mut self, state: C, depth: usize, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.is_out_of_time() {
+            return 0;
+        }
+        if depth == 0 {
+            return self.quiescence(state, ply, alpha, beta);
+        }
+
+        let key = state.zobrist_hash();
+        let entry = self.table.get(key);
+        if let Some(entry) = entry.filter(|entry| entry.depth as usize >= depth && ply > 0) {
+            let score = score_from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => (),
+            }
+        }
+
+        let moves = state.get_legal_compound_moves();
+        // a player that can not move loses, the sooner the worse
+        if moves.is_empty() {
+            return -WIN_SCORE + ply as i32;
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -WIN_SCORE;
+        let mut best_move = 0;
+        for index in self.order_moves(&moves, entry.map(|entry| entry.best_move as usize), ply) {
+            let mv = &moves[index];
+            let score = -self.negamax(state.make_compound_move(mv), depth - 1, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = index;
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                // quiet moves that cause cutoffs are likely to do so in sibling positions as well
+                if !mv.is_capture() {
+                    self.store_killer(move_key(mv), ply);
+                    self.history[square_index(mv.from)][square_index(mv.to())] += (depth * depth) as u32;
+                }
+                break;
+            }
+        }
+
+        if ply == 0 {
+            self.root_move = best_move;
+        }
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let score = score_to_table(best_score, ply);
+        self.table.insert(Entry { key, depth: depth as u8, score, bound, best_move: best_move as u8 });
+
+        best_score
+    }
+
+    // only resolves pending captures, the position is evaluated once it is quiet
+    fn quiescence<C: Checkers>(&mut self, state: C, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let moves = state.get_legal_compound_moves();
+        if moves.is_empty() {
+            return -WIN_SCORE + ply as i32;
+        }
+        if !moves[0].is_capture() {
+            return self.evaluator.evaluate(&state);
+        }
+
+        let mut best_score = -WIN_SCORE;
+        for mv in &moves {
+            let score = -self.quiescence(state.make_compound_move(mv), ply + 1, -beta, -alpha);
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+
+    // the indices of the moves, the most promising first
+    fn order_moves(&self, moves: &[CompoundMove], table_move: Option<usize>, ply: usize) -> Vec<usize> {
+        let killers = self.killers.get(ply).copied().unwrap_or([None; 2]);
+        let mut indices = (0..moves.len()).collect::<Vec<_>>();
+        indices.sort_by_cached_key(|index| {
+            let mv = &moves[*index];
+            let priority = if Some(*index) == table_move {
+                0
+            } else if mv.is_capture() {
+                1
+            } else if killers.contains(&Some(move_key(mv))) {
+                2
+            } else {
+                3
+            };
+            // more captures first, and the best history scores first
+            (priority, std::cmp::Reverse(mv.captured.len()), std::cmp::Reverse(self.history[square_index(mv.from)][square_index(mv.to())]))
+        });
+        indices
+    }
+
+    fn store_killer(&mut self, key: MoveKey, ply: usize) {
+        let Some(killers) = self.killers.get_mut(ply) else { return };
+        if killers[0] != Some(key) {
+            killers[1] = killers[0];
+            killers[0] = Some(key);
+        }
+    }
+}
+
+impl<C: Checkers, E: Evaluator> Agent<C> for AlphaBetaAgent<E> {
+    fn get_best_move(&mut self, root: C) -> Move {
+        let (mv, _) = self.search(root).expect("the game is over, there is no move to make");
+        // the rest of a capture chain is searched again once it is the agent's turn to continue it
+        Move { from: mv.from, to: mv.path[0], is_skip_move: mv.is_capture() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, fen, slow};
+
+    #[test]
+    fn test_transposition_table() {
+        let mut table = TranspositionTable::new(16);
+        let entry = Entry { key: 5, depth: 3, score: 10, bound: Bound::Exact, best_move: 1 };
+        table.insert(entry);
+        assert_eq!(table.get(5).unwrap().score, 10);
+        assert!(table.get(21).is_none());
+
+        // shallower results do not replace deeper ones
+        table.insert(Entry { depth: 2, score: 20, ..entry });
+        assert_eq!(table.get(5).unwrap().score, 10);
+        table.insert(Entry { key: 21, ..entry });
+        assert_eq!(table.get(21).unwrap().key, 21);
+    }
+
+    #[test]
+    fn test_mate_scores() {
+        // a win in 5 plies from the root, stored at ply 2, is a win in 4 plies when reached at ply 1
+        let score = score_to_table(WIN_SCORE - 5, 2);
+        assert_eq!(score, WIN_SCORE - 3);
+        assert_eq!(score_from_table(score, 1), WIN_SCORE - 4);
+        assert_eq!(score_from_table(score_to_table(-WIN_SCORE + 5, 2), 1), -WIN_SCORE + 4);
+        assert_eq!(score_from_table(score_to_table(120, 2), 1), 120);
+    }
+
+    #[test]
+    fn test_finds_double_capture() {
+        // 14 can capture 18 and 27 in one go, or 17 alone
+        let state: fast::BoardState = fen::from_fen("B:W17,18,27:B5,14").unwrap();
+        let mut agent = AlphaBetaAgent::new(4, None, 1 << 12);
+        let (mv, score) = agent.search(state).unwrap();
+        assert_eq!(mv.to_string(), "14x23x32");
+        assert!(score > 0);
+    }
+
+    #[test]
+    fn test_finds_forced_win() {
+        let state: fast::BoardState = fen::from_fen("B:W18:B14").unwrap();
+        let mut agent = AlphaBetaAgent::new(4, None, 1 << 12);
+        let (mv, score) = agent.search(state).unwrap();
+        assert_eq!(mv.to_string(), "14x23");
+        assert_eq!(score, WIN_SCORE - 1);
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let mut agent = AlphaBetaAgent::new(4, None, 1 << 16);
+        let slow_result = agent.search(slow::BoardState::default()).unwrap();
+        let fast_result = agent.search(<fast::BoardState as Checkers>::default()).unwrap();
+        assert_eq!(slow_result, fast_result);
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let mut agent = AlphaBetaAgent::new(100, Some(Duration::from_millis(50)), 1 << 16);
+        let start = Instant::now();
+        let mv = Agent::<fast::BoardState>::get_best_move(&mut agent, Checkers::default());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(Checkers::get_legal_moves(&<fast::BoardState as Checkers>::default()).contains(&mv));
+    }
+}
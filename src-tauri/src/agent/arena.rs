@@ -0,0 +1,72 @@
+// plays agents against each other, for comparing their strength
+use crate::board::{Checkers, GameOutcome, Player};
+use crate::board::history::GameHistory;
+use super::Agent;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Score {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+// `human` moves first, games that are still going after `max_plies` steps count as draws
+pub fn play<C: Checkers>(
+    human: &mut impl Agent<C>,
+    cpu: &mut impl Agent<C>,
+    start: C,
+    max_plies: usize,
+) -> GameOutcome {
+    let mut history = GameHistory::new(start);
+    for _ in 0..max_plies {
+        if history.get_outcome() != GameOutcome::Ongoing {
+            break;
+        }
+        let state = history.current();
+        let mv = match state.get_turn() {
+            Player::Human => human.get_best_move(state),
+            Player::Cpu => cpu.get_best_move(state),
+        };
+        history.make_move(mv);
+    }
+    history.get_outcome()
+}
+
+// plays `games` games from the starting position, alternating who moves first, and scores them for `first`
+pub fn compare<C: Checkers>(
+    first: &mut impl Agent<C>,
+    second: &mut impl Agent<C>,
+    games: usize,
+    max_plies: usize,
+) -> Score {
+    let mut score = Score::default();
+    for game in 0..games {
+        let (outcome, first_player) = match game % 2 {
+            0 => (play(first, second, C::default(), max_plies), Player::Human),
+            _ => (play(second, first, C::default(), max_plies), Player::Cpu),
+        };
+        match outcome {
+            GameOutcome::Win(player) if player == first_player => score.wins += 1,
+            GameOutcome::Win(_) => score.losses += 1,
+            _ => score.draws += 1,
+        }
+    }
+    score
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::FirstMoveAgent;
+    use crate::board::fast;
+
+    #[test]
+    fn test_compare() {
+        // the same deterministic agent on both sides plays the same game twice, with the colors swapped
+        let score = compare::<fast::BoardState>(&mut FirstMoveAgent, &mut FirstMoveAgent, 2, 300);
+        assert_eq!(score.wins + score.draws + score.losses, 2);
+        assert_eq!(score.wins, score.losses);
+    }
+}
@@ -0,0 +1,221 @@
+// an opening book: for positions from the start of games, the moves that were played in them along with how often
+// and how well. positions are keyed by their zobrist hash, and moves are single steps (like `Checkers::make_move`),
+// so positions in the middle of a capture chain have entries as well
+// books are built from PDN game collections or self-play, and stored as JSON
+use std::collections::HashMap;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::board::{fast, Checkers, GameOutcome, Move, Player};
+use crate::board::history::GameHistory;
+use crate::pdn::{self, PdnError, PdnResult};
+use super::Agent;
+
+
+// how many single steps of each game go into the book by default
+pub const DEFAULT_MAX_PLIES: usize = 12;
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize, serde::Serialize)]
+pub struct BookMove {
+    pub mv: Move,
+    // how likely the move is to be picked, relative to the other moves in the same position
+    pub weight: u32,
+    // the results of the games the move was played in, for the player that played it
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct OpeningBook {
+    positions: HashMap<u64, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn get<C: Checkers>(&self, state: C) -> &[BookMove] {
+        self.positions.get(&state.zobrist_hash()).map_or(&[], |moves| moves.as_slice())
+    }
+
+    // adds the first `max_plies` steps of a game, `winner` being `None` for draws and unfinished games
+    pub fn add_game<C: Checkers>(&mut self, start: C, moves: &[Move], winner: Option<Player>, max_plies: usize) {
+        let mut state = start;
+        for mv in moves.iter().take(max_plies) {
+            let player = state.get_turn();
+            let book_moves = self.positions.entry(state.zobrist_hash()).or_default();
+            let index = match book_moves.iter().position(|book_move| book_move.mv == *mv) {
+                Some(index) => index,
+                None => {
+                    book_moves.push(BookMove { mv: *mv, weight: 0, wins: 0, draws: 0, losses: 0 });
+                    book_moves.len() - 1
+                }
+            };
+
+            let book_move = &mut book_moves[index];
+            book_move.weight += 1;
+            match winner {
+                Some(winner) if winner == player => book_move.wins += 1,
+                Some(_) => book_move.losses += 1,
+                None => book_move.draws += 1,
+            }
+
+            state = state.make_move(*mv);
+        }
+    }
+
+    // every game in the collection has to replay without errors
+    pub fn add_pdn(&mut self, text: &str, max_plies: usize) -> Result<(), PdnError> {
+        for game in pdn::read(text)? {
+            let start = game.start::<fast::BoardState>()?;
+            let (moves, _) = game.replay::<fast::BoardState>()?;
+            let steps = moves.iter().flat_map(|mv| mv.steps()).collect::<Vec<_>>();
+            let winner = match game.result {
+                PdnResult::Win(player) => Some(player),
+                PdnResult::Draw | PdnResult::Unknown => None,
+            };
+            self.add_game(start, &steps, winner, max_plies);
+        }
+        Ok(())
+    }
+
+    pub fn from_pdn(text: &str, max_plies: usize) -> Result<Self, PdnError> {
+        let mut book = Self::default();
+        book.add_pdn(text, max_plies)?;
+        Ok(book)
+    }
+
+    // plays `games` games of the agent against itself, the first `random_plies` steps being random so the games
+    // (and the book) branch out, and adds them once they are over (or `max_game_plies` steps long)
+    pub fn add_self_play<C: Checkers, A: Agent<C>>(
+        &mut self,
+        agent: &mut A,
+        games: usize,
+        random_plies: usize,
+        max_game_plies: usize,
+        random: &mut impl Rng,
+    ) {
+        for _ in 0..games {
+            let mut history = GameHistory::new(C::default());
+            let mut moves = vec![];
+            while history.get_outcome() == GameOutcome::Ongoing && moves.len() < max_game_plies {
+                let state = history.current();
+                let mv = match moves.len() < random_plies {
+                    true => *state.get_legal_move_list().choose(random).unwrap(),
+                    false => agent.get_best_move(state),
+                };
+                history.make_move(mv);
+                moves.push(mv);
+            }
+
+            let winner = match history.get_outcome() {
+                GameOutcome::Win(player) => Some(player),
+                _ => None,
+            };
+            self.add_game(C::default(), &moves, winner, DEFAULT_MAX_PLIES);
+        }
+    }
+
+    // a weighted random choice between the book moves that are legal in this position
+    // the legality check guards against hash collisions (and books made for different rules)
+    pub fn pick<C: Checkers>(&self, state: C, random: &mut impl Rng) -> Option<Move> {
+        let legal_moves = state.get_legal_move_list();
+        let candidates = self.get(state).iter()
+            .filter(|book_move| book_move.weight > 0 && legal_moves.contains(&book_move.mv))
+            .collect::<Vec<_>>();
+        candidates.choose_weighted(random, |book_move| book_move.weight).ok().map(|book_move| book_move.mv)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::FirstMoveAgent;
+    use crate::board::slow;
+    use crate::public;
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    const GAMES: &str = r#"
+[Result "0-1"]
+1. 11-15 23-19 2. 8-11 22-17 0-1
+
+[Result "1-0"]
+1. 11-15 23-19 2. 9-13 1-0
+
+[Result "1/2-1/2"]
+1. 9-14 22-18 1/2-1/2
+"#;
+
+    #[test]
+    fn test_from_pdn() {
+        let book = OpeningBook::from_pdn(GAMES, DEFAULT_MAX_PLIES).unwrap();
+        let start = slow::BoardState::default();
+
+        let mut moves = book.get(start).to_vec();
+        moves.sort_by_key(|book_move| book_move.weight);
+        assert_eq!(moves.len(), 2);
+        // 11-15 was played twice, and black won one of those games
+        assert_eq!((moves[1].weight, moves[1].wins, moves[1].losses), (2, 1, 1));
+        assert_eq!((moves[0].weight, moves[0].draws), (1, 1));
+
+        // the book works across backends, since the keys are zobrist hashes
+        let state = start.make_move(moves[1].mv);
+        assert_eq!(book.get(state).len(), 1);
+        assert_eq!(book.get(fast::BoardState::from(public::BoardState::from(state))).len(), 1);
+
+        assert!(OpeningBook::from_pdn("1. 11-15 11-15 *", DEFAULT_MAX_PLIES).is_err());
+        assert!(matches!(
+            OpeningBook::from_pdn("[FEN \"B:W1:B32\"] *", DEFAULT_MAX_PLIES),
+            Err(PdnError::InvalidBoard(_))
+        ));
+    }
+
+    #[test]
+    fn test_pick() {
+        let book = OpeningBook::from_pdn(GAMES, DEFAULT_MAX_PLIES).unwrap();
+        let mut random = SmallRng::seed_from_u64(17);
+        let start = slow::BoardState::default();
+
+        let picks = (0..300).map(|_| book.pick(start, &mut random).unwrap()).collect::<Vec<_>>();
+        let played = book.get(start).iter().map(|book_move| book_move.mv).collect::<Vec<_>>();
+        assert!(picks.iter().all(|mv| played.contains(mv)));
+        // 11-15 has twice the weight of 9-14
+        let most_played = book.get(start).iter().max_by_key(|book_move| book_move.weight).unwrap().mv;
+        assert!(picks.iter().filter(|mv| **mv == most_played).count() > 150);
+
+        // positions that are not in the book
+        let state = start.make_move(start.get_legal_moves()[0]).make_move(Move { from: (2, 7), to: (3, 6), is_skip_move: false });
+        assert_eq!(book.pick(state, &mut random), None);
+    }
+
+    #[test]
+    fn test_self_play() {
+        let mut book = OpeningBook::default();
+        let mut random = SmallRng::seed_from_u64(17);
+        book.add_self_play::<fast::BoardState, _>(&mut FirstMoveAgent, 5, 2, 200, &mut random);
+        let total_weight: u32 = book.get(<fast::BoardState as Checkers>::default()).iter().map(|book_move| book_move.weight).sum();
+        assert_eq!(total_weight, 5);
+    }
+
+    #[test]
+    fn test_json() {
+        let book = OpeningBook::from_pdn(GAMES, DEFAULT_MAX_PLIES).unwrap();
+        assert_eq!(OpeningBook::from_json(&book.to_json()).unwrap(), book);
+        assert!(OpeningBook::from_json("{").is_err());
+    }
+}
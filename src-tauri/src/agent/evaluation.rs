@@ -0,0 +1,194 @@
+// static evaluation of positions, for searches that stop before the end of the game and for cutting rollouts short
+// evaluators only read the position through `Checkers`, so they work on every backend
+use crate::board::{Checkers, Player};
+use crate::public;
+
+
+pub trait Evaluator {
+    // how good the position is for the player to move, zero being even
+    fn evaluate<C: Checkers>(&self, state: &C) -> i32;
+
+    // what a single man is worth, the unit rollouts that are cut short are adjudicated in
+    fn man_value(&self) -> i32 {
+        100
+    }
+}
+
+// the terms of the hand-crafted evaluation, in hundredths of a man by default
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct Weights {
+    pub man: i32,
+    pub king: i32,
+    // men still on their own back row, which keep the opponent from crowning there
+    pub back_rank: i32,
+    // pieces on the eight central squares
+    pub center: i32,
+    // each legal move, counted for both players as if it were their turn
+    pub mobility: i32,
+    // men that no enemy piece stands in front of, so nothing can stop them from being crowned
+    pub runaway: i32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            man: 100,
+            king: 160,
+            back_rank: 10,
+            center: 5,
+            mobility: 2,
+            runaway: 40,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
+pub struct HandCrafted {
+    pub weights: Weights,
+}
+
+impl HandCrafted {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+}
+
+fn forward(player: Player) -> i8 {
+    match player {
+        Player::Human => -1,
+        Player::Cpu => 1,
+    }
+}
+
+fn back_row(player: Player) -> u8 {
+    match player {
+        Player::Human => 7,
+        Player::Cpu => 0,
+    }
+}
+
+fn is_center((row, col): (u8, u8)) -> bool {
+    (3..=4).contains(&row) && (2..=5).contains(&col)
+}
+
+// whether the cone in front of the man, the squares it could possibly be intercepted from, is free of enemy pieces
+fn is_runaway<C: Checkers>(state: &C, (row, col): (u8, u8), player: Player) -> bool {
+    let mut distance = 1;
+    let mut ahead = row as i8 + forward(player);
+    while (0..8).contains(&ahead) {
+        for ahead_col in col as i8 - distance..=col as i8 + distance {
+            if !(0..8).contains(&ahead_col) {
+                continue;
+            }
+            if state.get_tile((ahead as u8, ahead_col as u8)).is_some_and(|tile| tile.player != player) {
+                return false;
+            }
+        }
+        distance += 1;
+        ahead += forward(player);
+    }
+    true
+}
+
+// the number of moves the player would have if it were their turn
+fn mobility<C: Checkers>(state: &C, player: Player) -> i32 {
+    if state.get_turn() == player {
+        return state.get_legal_move_list().len() as i32;
+    }
+    let mut position: public::BoardState = (*state).into();
+    position.turn = player;
+    position.capturing = None;
+    C::from(position).get_legal_move_list().len() as i32
+}
+
+impl Evaluator for HandCrafted {
+    fn evaluate<C: Checkers>(&self, state: &C) -> i32 {
+        let weights = &self.weights;
+        let turn = state.get_turn();
+
+        let mut score = 0;
+        for row in 0..8 {
+            for col in (1 - row % 2..8).step_by(2) {
+                let Some(tile) = state.get_tile((row, col)) else { continue };
+
+                let mut value = if tile.is_king { weights.king } else { weights.man };
+                if !tile.is_king && row == back_row(tile.player) {
+                    value += weights.back_rank;
+                }
+                if is_center((row, col)) {
+                    value += weights.center;
+                }
+                if !tile.is_king && is_runaway(state, (row, col), tile.player) {
+                    value += weights.runaway;
+                }
+
+                score += if tile.player == turn { value } else { -value };
+            }
+        }
+
+        if weights.mobility != 0 {
+            score += weights.mobility * (mobility(state, turn) - mobility(state, !turn));
+        }
+        score
+    }
+
+    fn man_value(&self) -> i32 {
+        self.weights.man
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, fen, slow};
+
+    fn evaluate(text: &str) -> (i32, i32) {
+        let evaluator = HandCrafted::default();
+        let slow_state: slow::BoardState = fen::from_fen(text).unwrap();
+        let fast_state: fast::BoardState = fen::from_fen(text).unwrap();
+        (evaluator.evaluate(&slow_state), evaluator.evaluate(&fast_state))
+    }
+
+    #[test]
+    fn test_symmetric() {
+        assert_eq!(HandCrafted::default().evaluate(&slow::BoardState::default()), 0);
+        assert_eq!(evaluate("B:W21-32:B1-12"), (0, 0));
+        // the same position with the other player to move is worth the opposite
+        let (black, _) = evaluate("B:W18,K30:B14,15");
+        let (white, _) = evaluate("W:W18,K30:B14,15");
+        assert_eq!(black, -white);
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        for text in ["B:W18,K30:B14,15", "W:W5,22,23,K10:B1,2,3,K29", "B:W21-32:B1-9,13,14,16"] {
+            let (slow_score, fast_score) = evaluate(text);
+            assert_eq!(slow_score, fast_score, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_terms() {
+        let weights = Weights { back_rank: 0, center: 0, mobility: 0, runaway: 0, ..Weights::default() };
+        let material = HandCrafted::new(weights);
+        let state: fast::BoardState = fen::from_fen("B:WK30:B14,15").unwrap();
+        assert_eq!(material.evaluate(&state), 2 * weights.man - weights.king);
+
+        // the man on 28 has a free path to the other side, the one on 21 does not
+        let runaway = HandCrafted::new(Weights { runaway: 40, ..weights });
+        let state: fast::BoardState = fen::from_fen("W:W21,28:B17").unwrap();
+        let runaways = runaway.evaluate(&state) - material.evaluate(&state);
+        assert_eq!(runaways, 40);
+
+        let back_rank = HandCrafted::new(Weights { back_rank: 10, ..weights });
+        let state: fast::BoardState = fen::from_fen("B:W25:B1,2,14").unwrap();
+        assert_eq!(back_rank.evaluate(&state) - material.evaluate(&state), 20);
+    }
+
+    #[test]
+    fn test_weights_json() {
+        let weights: Weights = serde_json::from_str(&serde_json::to_string(&Weights::default()).unwrap()).unwrap();
+        assert_eq!(weights, Weights::default());
+    }
+}
@@ -1,133 +1,361 @@
-use crate::board::{Checkers, Move, Player};
+use crate::board::{Checkers, GameOutcome, Move, Player};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Instant;
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
 use rand::prelude::*;
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
-use super::Agent;
+use super::{Agent, MctsConfig};
+use super::evaluation::{Evaluator, HandCrafted};
+use super::rollout::rollout;
 
-#[derive(Default)]
+// the results of the playouts through a position, from the perspective of the player that moved into it
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub struct Statistics {
     pub visits: usize,
-    pub human_wins: usize,
-    pub cpu_wins: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
 }
 
-const MAX_ROLLOUTS: usize = 500;
+impl Statistics {
+    // the average reward, a win being worth 1 and a draw 0.5
+    pub fn value(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.visits as f64
+    }
+
+    // counts the result of a playout, `None` being a draw. the visit is counted separately
+    pub fn record(&mut self, winner: Option<Player>, mover: Player) {
+        match winner {
+            Some(winner) if winner == mover => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+// the positions a move leads to. when one of them leaves the opponent without moves, it is the only one worth
+// searching, so the others are left out
+pub fn expand<C: Checkers>(state: C) -> Vec<(Move, C)> {
+    let children = state.get_legal_move_list().iter().map(|mv| (*mv, state.make_move(*mv))).collect::<Vec<_>>();
+    match children.iter().find(|(_, child)| child.get_outcome() == GameOutcome::Win(state.get_turn())) {
+        Some(winning) => vec![*winning],
+        None => children,
+    }
+}
+
+// a hash map split into separately locked shards by zobrist key, so threads working on different positions rarely
+// wait for each other
+struct ShardedMap<C: Checkers, V> {
+    shards: Vec<Mutex<FnvHashMap<C, V>>>,
+}
+
+impl<C: Checkers, V> ShardedMap<C, V> {
+    fn new() -> Self {
+        Self { shards: (0..SHARDS).map(|_| Mutex::new(FnvHashMap::default())).collect() }
+    }
+
+    fn shard(&self, key: &C) -> MutexGuard<'_, FnvHashMap<C, V>> {
+        self.shards[key.zobrist_hash() as usize % SHARDS].lock().unwrap()
+    }
+
+    fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+
+    fn retain(&mut self, keep: impl Fn(&C) -> bool) {
+        for shard in &mut self.shards {
+            shard.get_mut().unwrap().retain(|key, _| keep(key));
+        }
+    }
+}
+
+const SHARDS: usize = 64;
 
 
-pub struct MctsHashAgent<C: Checkers> {
-    stats: FnvHashMap<C, Statistics>,
-    children: FnvHashMap<C, FnvHashSet<C>>,
+pub struct MctsHashAgent<C: Checkers, E = HandCrafted> {
+    stats: ShardedMap<C, Statistics>,
+    children: ShardedMap<C, Arc<FnvHashSet<C>>>,
     random: rand::rngs::SmallRng,
+    // the iterations are split between `config.threads` threads, which share the statistics
+    pub config: MctsConfig,
+    // guides and adjudicates the rollouts, depending on `config.rollout_policy`
+    pub evaluator: E,
 }
 
 impl<C: Checkers> Default for MctsHashAgent<C> {
     fn default() -> Self {
+        Self::new(MctsConfig::default())
+    }
+}
+
+impl<C: Checkers> MctsHashAgent<C> {
+    pub fn new(config: MctsConfig) -> Self {
+        Self::with_evaluator(HandCrafted::default(), config)
+    }
+}
+
+impl<C: Checkers, E: Evaluator> MctsHashAgent<C, E> {
+    pub fn with_evaluator(evaluator: E, config: MctsConfig) -> Self {
         Self {
-            stats: FnvHashMap::default(),
-            children: FnvHashMap::default(),
-            random: SmallRng::from_seed([6; 32]),
+            stats: ShardedMap::new(),
+            children: ShardedMap::new(),
+            random: SmallRng::seed_from_u64(config.seed),
+            config,
+            evaluator,
         }
     }
+
+    // the number of positions with statistics
+    pub fn len(&self) -> usize {
+        self.stats.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // drops every position that can not be reached from the root through the children expanded so far, which
+    // includes everything before the root, since pieces never come back and men never move backwards
+    pub fn prune(&mut self, root: C) {
+        let mut reachable = FnvHashSet::default();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if !reachable.insert(node) {
+                continue;
+            }
+            if let Some(children) = self.children.shard(&node).get(&node) {
+                stack.extend(children.iter().copied());
+            }
+        }
+        self.stats.retain(|node| reachable.contains(node));
+        self.children.retain(|node| reachable.contains(node));
+    }
 }
 
-impl<C: Checkers> Agent<C> for MctsHashAgent<C> {
+impl<C: Checkers + Send + Sync, E: Evaluator + Sync> Agent<C> for MctsHashAgent<C, E> {
     fn get_best_move(&mut self, root: C) -> Move {
-        self.stats.insert(root, Statistics::default());
+        // the statistics of earlier searches are kept for the positions that can still come up, so a search picks
+        // up where the previous one (on an earlier move of the same game) left off
+        self.prune(root);
+        self.stats.shard(&root).entry(root).or_default();
 
-        for _ in 0..20_000 {
-            let path = self.select(root);
-            let reward = self.rollout(*path.last().unwrap());
-            self.backpropagate(path, reward);
+        let config = self.config;
+        let threads = config.threads.unwrap_or(1).max(1);
+        let deadline = config.time_budget().map(|budget| Instant::now() + budget);
+        let seeds = (0..threads).map(|_| self.random.gen()).collect::<Vec<u64>>();
+        let agent = &*self;
+        let search = |thread: usize| {
+            let mut random = SmallRng::seed_from_u64(seeds[thread]);
+            // the first threads take the remainder
+            let iterations = config.iterations / threads + usize::from(thread < config.iterations % threads);
+            let mut iteration = 0;
+            while deadline.map_or(iteration < iterations, |deadline| Instant::now() < deadline) {
+                let path = agent.select(root);
+                let leaf = *path.last().unwrap();
+                // going around in a cycle is a draw by repetition
+                let winner = match path[..path.len() - 1].contains(&leaf) {
+                    true => None,
+                    false => rollout(leaf, config.rollout_policy, config.max_rollout_plies, &mut random, &agent.evaluator),
+                };
+                agent.backpropagate(&path, winner);
+                iteration += 1;
+            }
+        };
+        if threads == 1 {
+            search(0);
+        } else {
+            thread::scope(|scope| {
+                for thread in 0..threads {
+                    scope.spawn(move || search(thread));
+                }
+            });
         }
 
-        let possible_moves = root.get_legal_moves();
+        let possible_moves = root.get_legal_move_list();
         // just return max visit count
-        let best_move = possible_moves.iter().max_by_key(|mv| {
-            let child = root.make_move(**mv);
-            self.stats.get(&child).unwrap().visits
-        }).unwrap();
+        let best_move = possible_moves.iter().max_by_key(|mv| self.visits(&root.make_move(**mv))).unwrap();
         *best_move
     }
 }
 
 
-impl<C: Checkers> MctsHashAgent<C> {
-    fn select(&mut self, root: C) -> Vec<C> {
+impl<C: Checkers, E: Evaluator> MctsHashAgent<C, E> {
+    fn visits(&self, node: &C) -> usize {
+        self.stats.shard(node).get(node).map_or(0, |stats| stats.visits)
+    }
+
+    // counts a visit to the node along with a loss for the player that moved into it, so other threads are steered
+    // away from it until the rollout is done. `backpropagate` takes the loss back
+    fn add_virtual_loss(&self, node: C, mover: Option<Player>) {
+        let mut shard = self.stats.shard(&node);
+        let stats = shard.entry(node).or_default();
+        stats.visits += 1;
+        if mover.is_some() {
+            stats.losses += 1;
+        }
+    }
+
+    fn select(&self, root: C) -> Vec<C> {
         let mut path = vec![root];
+        self.add_virtual_loss(root, None);
         loop {
             let node = *path.last().unwrap();
+            // the outcome of a finished game is known, `rollout` returns it right away
             if node.is_game_over() {
                 break;
             }
-            
-            if !self.stats.contains_key(&node) {
-                self.stats.insert(node, Statistics { visits: 0, human_wins: 0, cpu_wins: 0 });
-            }
-            if !self.children.contains_key(&node) {
-                let children = node.get_legal_moves().iter().map(|mv| node.make_move(*mv)).collect();
-                self.children.insert(node, children);
+            // kings can move back and forth, and once every position of such a cycle has been visited the
+            // selection would go around it forever
+            if path[..path.len() - 1].contains(&node) {
+                break;
             }
-            let children = self.children.get(&node).unwrap();
-            let unvisited_children = children.iter().filter(|child| !self.stats.contains_key(child));
+
+            let children = self.children.shard(&node).entry(node).or_insert_with(|| {
+                Arc::new(expand(node).into_iter().map(|(_, child)| child).collect())
+            }).clone();
 
             // if there are unvisited children, select one and return immediately
-            if let Some(unvisited_child) = unvisited_children.last() {
-                self.stats.insert(*unvisited_child, Statistics::default());
+            let unvisited_child = children.iter().filter(|child| !self.stats.shard(child).contains_key(child)).last();
+            if let Some(unvisited_child) = unvisited_child {
+                self.add_virtual_loss(*unvisited_child, Some(node.get_turn()));
                 path.push(*unvisited_child);
                 return path;
             }
 
             // select by max UCT
+            let parent_visits = self.visits(&node);
             let best_child_idx = children.iter().map(|child| {
-                let parent_stats = self.stats.get(&node).unwrap();
-                let child_stats = self.stats.get(child).unwrap();
-
-                let (wins, losses) = match node.get_turn() {
-                    Player::Human => (child_stats.human_wins, child_stats.cpu_wins),
-                    Player::Cpu => (child_stats.cpu_wins, child_stats.human_wins),
-                };
-                let exploitation = (wins as f64 - losses as f64) / child_stats.visits as f64;
-                let exploration = (2.0f64 * (parent_stats.visits as f64).ln() / child_stats.visits as f64).sqrt();
-                let uct = exploitation + 1.41 * exploration;
-                uct
+                let child_stats = *self.stats.shard(child).get(child).unwrap();
+                let exploitation = child_stats.value();
+                let exploration = (2.0f64 * (parent_visits as f64).ln() / child_stats.visits as f64).sqrt();
+                exploitation + self.config.exploration * exploration
             }).position_max_by(|a, b| a.partial_cmp(b).unwrap()).unwrap();
 
-            path.push(*children.iter().nth(best_child_idx).unwrap());
+            let child = *children.iter().nth(best_child_idx).unwrap();
+            self.add_virtual_loss(child, Some(node.get_turn()));
+            path.push(child);
         }
 
         path
     }
 
-    fn rollout(&mut self, state: C) -> Option<Player> {
-        let mut rollout_state = state.clone();
-        let mut counter = 0;
-        while !rollout_state.is_game_over() {
-            let possible_moves = rollout_state.get_legal_moves();
-            if possible_moves.is_empty() {
-                return None;
-            }
-            let mv = possible_moves.choose(&mut self.random).unwrap();
-            rollout_state = rollout_state.make_move(*mv);
-            counter += 1;
-
-            if counter > MAX_ROLLOUTS {
-                return None;
-            }
+    // the visits were counted during selection, only the virtual losses have to be replaced by the result
+    // the root is not moved into, so only its visits count
+    fn backpropagate(&self, path: &[C], winner: Option<Player>) {
+        for (parent, node) in path.iter().tuple_windows() {
+            let mut shard = self.stats.shard(node);
+            let stats = shard.get_mut(node).unwrap();
+            stats.losses -= 1;
+            stats.record(winner, parent.get_turn());
         }
-        rollout_state.get_winner()
     }
+}
 
-    fn backpropagate(&mut self, path: Vec<C>, result: Option<Player>) {
-        for node in path {
-            let stats = self.stats.get_mut(&node).unwrap();
-            stats.visits += 1;
-            match result {
-                Some(Player::Human) => stats.human_wins += 1,
-                Some(Player::Cpu) => stats.cpu_wins += 1,
-                _ => (),
-            }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, fen};
+
+    #[test]
+    fn test_threads() {
+        let root = <fast::BoardState as Checkers>::default();
+        let mut agent = MctsHashAgent::new(MctsConfig { threads: Some(4), iterations: 2_001, ..MctsConfig::default() });
+        let mv = agent.get_best_move(root);
+        assert!(Checkers::get_legal_moves(&root).contains(&mv));
+
+        // every iteration visited the root and one of its children once
+        assert_eq!(agent.visits(&root), 2_001);
+        let children = agent.children.shard(&root).get(&root).unwrap().clone();
+        assert_eq!(children.iter().map(|child| agent.visits(child)).sum::<usize>(), 2_001);
+
+        // no virtual losses were left behind, which would show up as more results than visits
+        for child in children.iter() {
+            let shard = agent.stats.shard(child);
+            let stats = shard.get(child).unwrap();
+            assert_eq!(stats.wins + stats.draws + stats.losses, stats.visits);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_statistics() {
+        let mut stats = Statistics { visits: 4, ..Statistics::default() };
+        stats.record(Some(Player::Cpu), Player::Cpu);
+        stats.record(None, Player::Cpu);
+        stats.record(None, Player::Cpu);
+        stats.record(Some(Player::Human), Player::Cpu);
+        assert_eq!((stats.wins, stats.draws, stats.losses), (1, 2, 1));
+        assert_eq!(stats.value(), 0.5);
+    }
+
+    #[test]
+    fn test_terminal_children() {
+        // 19-24 and 20-24 block white's last man, only the first of them is kept
+        let state: fast::BoardState = fen::from_fen("B:W27:B18,19,20,23,K32").unwrap();
+        let children = expand(state);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].1.get_outcome(), GameOutcome::Win(Player::Human));
+
+        let mut agent = MctsHashAgent::new(MctsConfig { iterations: 50, ..MctsConfig::default() });
+        assert_eq!(agent.get_best_move(state), children[0].0);
+        // every playout through the blocked position was a win for the player that moved into it
+        let stats = *agent.stats.shard(&children[0].1).get(&children[0].1).unwrap();
+        assert_eq!((stats.wins, stats.visits), (50, 50));
+    }
+
+    #[test]
+    fn test_reuse() {
+        let root = <fast::BoardState as Checkers>::default();
+        let mut agent = MctsHashAgent::new(MctsConfig { iterations: 2_000, ..MctsConfig::default() });
+        let mv = agent.get_best_move(root);
+
+        // the reply the search expects most
+        let state = Checkers::make_move(&root, mv);
+        let children = agent.children.shard(&state).get(&state).unwrap().clone();
+        let reply = *children.iter().max_by_key(|child| agent.visits(child)).unwrap();
+        let (positions, visits) = (agent.len(), agent.visits(&reply));
+        assert!(visits > 0);
+
+        // only the subtree under the position reached is kept, and the search continues from its statistics
+        agent.get_best_move(reply);
+        assert_eq!(agent.visits(&reply), visits + 2_000);
+        assert!(agent.len() < positions + 2_000);
+        assert_eq!((agent.visits(&root), agent.visits(&state)), (0, 0));
+
+        // positions the agent knows nothing about start from scratch
+        agent.prune(root);
+        assert!(agent.is_empty());
+    }
+
+    #[test]
+    fn test_config() {
+        let config = MctsConfig::from_json(r#"{"iterations": 300, "exploration": 0.5, "seed": 17}"#).unwrap();
+        assert_eq!(config, MctsConfig { iterations: 300, exploration: 0.5, seed: 17, ..MctsConfig::default() });
+        // left for the caller to fill in
+        assert_eq!(config.threads, None);
+        assert!(MctsConfig::from_json(r#"{"iterations": -1}"#).is_err());
+
+        // the same seed gives the same search
+        let root = <fast::BoardState as Checkers>::default();
+        let searches = (0..2).map(|_| {
+            let mut agent = MctsHashAgent::new(config);
+            agent.get_best_move(root);
+            agent.visits(&Checkers::make_move(&root, Checkers::get_legal_moves(&root)[0]))
+        }).collect::<Vec<_>>();
+        assert_eq!(searches[0], searches[1]);
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let config = MctsConfig { iterations: usize::MAX, time_budget_ms: Some(100), ..MctsConfig::default() };
+        let mut agent = MctsHashAgent::new(config);
+        let start = Instant::now();
+        let root = <fast::BoardState as Checkers>::default();
+        agent.get_best_move(root);
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+        assert!(agent.visits(&root) > 0);
+    }
+}
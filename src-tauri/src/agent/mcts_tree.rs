@@ -0,0 +1,267 @@
+// monte carlo tree search over an explicit tree: nodes live in one arena and refer to each other by index, and every
+// edge keeps the move it stands for along with its own statistics
+// unlike `MctsHashAgent`, positions reached by different move orders get separate nodes, so a move never gets
+// mixed up with the statistics of a transposition
+use std::time::Instant;
+use crate::board::{Checkers, Move, Player};
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+use super::{Agent, MctsConfig};
+use super::evaluation::{Evaluator, HandCrafted};
+use super::mcts_hash::{self, Statistics};
+use super::rollout::rollout;
+
+
+pub struct Edge {
+    pub mv: Move,
+    // the node the move leads to, once it has been visited
+    pub child: Option<usize>,
+    pub stats: Statistics,
+}
+
+pub struct Node<C: Checkers> {
+    pub state: C,
+    pub visits: usize,
+    // the edges of the node are `edges[first_edge..first_edge + edge_count]`, filled in when it is first selected
+    first_edge: usize,
+    edge_count: usize,
+    is_expanded: bool,
+}
+
+pub struct MctsTreeAgent<C: Checkers, E = HandCrafted> {
+    nodes: Vec<Node<C>>,
+    edges: Vec<Edge>,
+    random: SmallRng,
+    // searches are single-threaded, `config.threads` is ignored
+    pub config: MctsConfig,
+    // guides and adjudicates the rollouts, depending on `config.rollout_policy`
+    pub evaluator: E,
+}
+
+impl<C: Checkers> Default for MctsTreeAgent<C> {
+    fn default() -> Self {
+        Self::new(MctsConfig::default())
+    }
+}
+
+impl<C: Checkers> MctsTreeAgent<C> {
+    pub fn new(config: MctsConfig) -> Self {
+        Self::with_evaluator(HandCrafted::default(), config)
+    }
+}
+
+impl<C: Checkers, E: Evaluator> MctsTreeAgent<C, E> {
+    pub fn with_evaluator(evaluator: E, config: MctsConfig) -> Self {
+        Self {
+            nodes: vec![],
+            edges: vec![],
+            random: SmallRng::seed_from_u64(config.seed),
+            config,
+            evaluator,
+        }
+    }
+}
+
+impl<C: Checkers, E: Evaluator> Agent<C> for MctsTreeAgent<C, E> {
+    fn get_best_move(&mut self, root: C) -> Move {
+        self.nodes.clear();
+        self.edges.clear();
+        self.add_node(root);
+
+        let deadline = self.config.time_budget().map(|budget| Instant::now() + budget);
+        let mut iteration = 0;
+        while deadline.map_or(iteration < self.config.iterations, |deadline| Instant::now() < deadline) {
+            let (leaf, path) = self.select();
+            let reward = rollout(
+                self.nodes[leaf].state,
+                self.config.rollout_policy,
+                self.config.max_rollout_plies,
+                &mut self.random,
+                &self.evaluator,
+            );
+            self.backpropagate(&path, reward);
+            iteration += 1;
+        }
+
+        // just return max visit count
+        self.root_edges().iter().max_by_key(|edge| edge.stats.visits).unwrap().mv
+    }
+}
+
+impl<C: Checkers, E: Evaluator> MctsTreeAgent<C, E> {
+    pub fn root_edges(&self) -> &[Edge] {
+        self.node_edges(0)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node_edges(&self, node: usize) -> &[Edge] {
+        let node = &self.nodes[node];
+        &self.edges[node.first_edge..node.first_edge + node.edge_count]
+    }
+
+    fn add_node(&mut self, state: C) -> usize {
+        self.nodes.push(Node { state, visits: 0, first_edge: 0, edge_count: 0, is_expanded: false });
+        self.nodes.len() - 1
+    }
+
+    fn expand(&mut self, node: usize) {
+        let children = mcts_hash::expand(self.nodes[node].state);
+        self.nodes[node].first_edge = self.edges.len();
+        self.nodes[node].edge_count = children.len();
+        self.nodes[node].is_expanded = true;
+        self.edges.extend(children.into_iter().map(|(mv, _)| Edge { mv, child: None, stats: Statistics::default() }));
+    }
+
+    // walks down the tree until a new node is added or the game is over, returning the last node and the edges taken
+    fn select(&mut self) -> (usize, Vec<usize>) {
+        let mut node = 0;
+        let mut path = vec![];
+        loop {
+            let state = self.nodes[node].state;
+            // the outcome of a finished game is known, `rollout` returns it right away
+            if state.is_game_over() {
+                return (node, path);
+            }
+            if !self.nodes[node].is_expanded {
+                self.expand(node);
+            }
+
+            let first_edge = self.nodes[node].first_edge;
+            let edges = first_edge..first_edge + self.nodes[node].edge_count;
+
+            // if there are unvisited moves, play one and return immediately
+            if let Some(edge) = edges.clone().rev().find(|edge| self.edges[*edge].child.is_none()) {
+                let child = self.add_node(state.make_move(self.edges[edge].mv));
+                self.edges[edge].child = Some(child);
+                path.push(edge);
+                return (child, path);
+            }
+
+            // select by max UCT
+            let parent_visits = self.nodes[node].visits;
+            let edge = edges.max_by(|a, b| self.uct(*a, parent_visits).partial_cmp(&self.uct(*b, parent_visits)).unwrap()).unwrap();
+
+            path.push(edge);
+            node = self.edges[edge].child.unwrap();
+        }
+    }
+
+    fn uct(&self, edge: usize, parent_visits: usize) -> f64 {
+        let stats = &self.edges[edge].stats;
+        let exploitation = stats.value();
+        let exploration = (2.0f64 * (parent_visits as f64).ln() / stats.visits as f64).sqrt();
+        exploitation + self.config.exploration * exploration
+    }
+
+    // the statistics of each edge are from the perspective of the player that made its move
+    fn backpropagate(&mut self, path: &[usize], winner: Option<Player>) {
+        let mut node = 0;
+        self.nodes[node].visits += 1;
+        for edge in path {
+            let mover = self.nodes[node].state.get_turn();
+            let edge = &mut self.edges[*edge];
+            edge.stats.visits += 1;
+            edge.stats.record(winner, mover);
+            node = edge.child.unwrap();
+            self.nodes[node].visits += 1;
+        }
+    }
+
+    // the moves of the most visited line, for inspecting the search
+    pub fn principal_variation(&self) -> Vec<Move> {
+        let mut line = vec![];
+        let mut node = 0;
+        while let Some(edge) = self.node_edges(node).iter().filter(|edge| edge.child.is_some()).max_by_key(|edge| edge.stats.visits) {
+            line.push(edge.mv);
+            node = edge.child.unwrap();
+        }
+        line
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{arena, FirstMoveAgent};
+    use crate::agent::mcts_hash::MctsHashAgent;
+    use crate::agent::rollout::RolloutPolicy;
+    use crate::board::{fast, fen, slow};
+    use itertools::Itertools;
+
+    #[test]
+    fn test_tree() {
+        let mut agent = MctsTreeAgent::new(MctsConfig { iterations: 500, ..MctsConfig::default() });
+        let root = <fast::BoardState as Checkers>::default();
+        let mv = agent.get_best_move(root);
+
+        // every root move is an edge, and each edge leads to the position its move leads to
+        let moves = agent.root_edges().iter().map(|edge| edge.mv).collect::<Vec<_>>();
+        assert_eq!(moves, Checkers::get_legal_moves(&root));
+        assert!(moves.contains(&mv));
+        for edge in agent.root_edges() {
+            assert_eq!(agent.nodes[edge.child.unwrap()].state, Checkers::make_move(&root, edge.mv));
+        }
+
+        // one node per iteration, plus the root
+        assert_eq!(agent.node_count(), 501);
+        assert_eq!(agent.nodes[0].visits, 500);
+        assert_eq!(agent.root_edges().iter().map(|edge| edge.stats.visits).sum::<usize>(), 500);
+    }
+
+    // checks that following the moves from the root leads to the state of every node, returning the states
+    fn walk<C: Checkers>(agent: &MctsTreeAgent<C>, node: usize, state: C, states: &mut Vec<C>) {
+        assert_eq!(agent.nodes[node].state, state);
+        states.push(state);
+        for edge in agent.node_edges(node) {
+            if let Some(child) = edge.child {
+                walk(agent, child, state.make_move(edge.mv), states);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transpositions() {
+        // the men on 9 and 10 can advance in either order
+        let mut agent = MctsTreeAgent::new(MctsConfig { iterations: 3_000, ..MctsConfig::default() });
+        let root: slow::BoardState = fen::from_fen("B:W29:B9,10").unwrap();
+        agent.get_best_move(root);
+
+        let mut states = vec![];
+        walk(&agent, 0, root, &mut states);
+        assert_eq!(states.len(), agent.node_count());
+        // some positions were reached more than once, and got a node each time
+        assert!(states.iter().unique().count() < states.len());
+    }
+
+    #[test]
+    fn test_finds_capture() {
+        let mut agent = MctsTreeAgent::new(MctsConfig { iterations: 2_000, ..MctsConfig::default() });
+        let state: fast::BoardState = fen::from_fen("B:W17,18,27:B5,14").unwrap();
+        assert_eq!(agent.get_best_move(state).to_string(), "14x23");
+        assert_eq!(agent.principal_variation()[..2].iter().map(|mv| mv.to_string()).collect::<Vec<_>>(), vec!["14x23", "23x32"]);
+    }
+
+    #[test]
+    fn test_against_first_move_agent() {
+        // a deterministic opponent, which a working search beats in every game. short rollouts keep the games fast
+        let config = MctsConfig { iterations: 200, rollout_policy: RolloutPolicy::Cutoff { plies: 20 }, ..MctsConfig::default() };
+        let mut tree = MctsTreeAgent::<fast::BoardState>::new(config);
+        let score = arena::compare(&mut tree, &mut FirstMoveAgent, 4, 300);
+        assert_eq!(score.wins, 4, "{:?}", score);
+    }
+
+    // a benchmark rather than a test, run it with `cargo test --release -- --ignored --nocapture`
+    // apart from the transpositions, both agents gather the same statistics, so they should be about as strong
+    #[test]
+    #[ignore]
+    fn test_against_hash_agent() {
+        let config = MctsConfig { iterations: 1_000, rollout_policy: RolloutPolicy::Cutoff { plies: 20 }, ..MctsConfig::default() };
+        let mut tree = MctsTreeAgent::<fast::BoardState>::new(config);
+        let mut hash = MctsHashAgent::new(config);
+        println!("tree against hash: {:?}", arena::compare(&mut tree, &mut hash, 40, 200));
+    }
+}
@@ -0,0 +1,227 @@
+// playouts for the monte carlo agents: how moves are picked on the way to the end of the game, and how games that
+// are cut short are scored
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use ts_rs::TS;
+use crate::board::{Checkers, GameOutcome, Move, Player};
+use crate::board::history::GameHistory;
+use crate::board::move_list::MoveList;
+use super::evaluation::Evaluator;
+
+
+#[derive(TS, Debug, Clone, Copy, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub enum RolloutPolicy {
+    // every legal move is equally likely
+    #[default]
+    Uniform,
+    // promotes whenever it can, and otherwise avoids moves that leave a piece to be captured. captures are
+    // mandatory, so they are always taken anyway
+    Greedy,
+    // plays the move the evaluator likes best, or a random one with probability `epsilon`
+    EpsilonGreedy { epsilon: f64 },
+    // plays uniformly random moves, but only for `plies` moves before the evaluator scores the position
+    Cutoff { plies: usize },
+}
+
+impl RolloutPolicy {
+    pub fn choose_move<C: Checkers, E: Evaluator>(&self, state: C, moves: &MoveList, random: &mut SmallRng, evaluator: &E) -> Move {
+        match self {
+            RolloutPolicy::Uniform | RolloutPolicy::Cutoff { .. } => *moves.choose(random).unwrap(),
+            RolloutPolicy::Greedy => choose_best(moves, random, |mv| {
+                let after = state.make_move(*mv);
+                if is_promotion(state, after, *mv) {
+                    2
+                } else if after.get_turn() == state.get_turn() || !can_capture(after) {
+                    1
+                } else {
+                    0
+                }
+            }),
+            RolloutPolicy::EpsilonGreedy { epsilon } => {
+                if random.gen_bool(epsilon.clamp(0.0, 1.0)) {
+                    return *moves.choose(random).unwrap();
+                }
+                choose_best(moves, random, |mv| {
+                    // the evaluation is for the player to move, which is still us in the middle of a capture
+                    let after = state.make_move(*mv);
+                    let score = evaluator.evaluate(&after);
+                    if after.get_turn() == state.get_turn() { score } else { -score }
+                })
+            }
+        }
+    }
+}
+
+// a random choice between the moves with the highest score
+fn choose_best(moves: &MoveList, random: &mut SmallRng, score: impl Fn(&Move) -> i32) -> Move {
+    let scores = moves.iter().map(&score).collect::<Vec<_>>();
+    let best = *scores.iter().max().unwrap();
+    let best_moves = moves.iter().zip(scores).filter(|(_, score)| *score == best).map(|(mv, _)| *mv).collect::<Vec<_>>();
+    *best_moves.choose(random).unwrap()
+}
+
+fn is_promotion<C: Checkers>(before: C, after: C, mv: Move) -> bool {
+    let was_king = before.get_tile(mv.from).is_some_and(|tile| tile.is_king);
+    let is_king = after.get_tile(mv.to).is_some_and(|tile| tile.is_king);
+    !was_king && is_king
+}
+
+// captures are mandatory, so if any move is a capture they all are
+fn can_capture<C: Checkers>(state: C) -> bool {
+    state.get_legal_move_list().first().is_some_and(|mv| mv.is_skip_move)
+}
+
+// plays moves picked by the policy until the game is over, returning the winner
+pub fn rollout<C: Checkers, E: Evaluator>(
+    state: C,
+    policy: RolloutPolicy,
+    max_plies: usize,
+    random: &mut SmallRng,
+    evaluator: &E,
+) -> Option<Player> {
+    let max_plies = match policy {
+        RolloutPolicy::Cutoff { plies } => plies.min(max_plies),
+        _ => max_plies,
+    };
+
+    let mut history = GameHistory::new(state);
+    let mut counter = 0;
+    loop {
+        match history.get_outcome() {
+            GameOutcome::Win(player) => return Some(player),
+            GameOutcome::Draw(_) => return None,
+            GameOutcome::Ongoing => (),
+        }
+        if counter >= max_plies {
+            return adjudicate(history.current(), evaluator);
+        }
+
+        // positions without legal moves are already decided by `get_outcome`
+        let state = history.current();
+        let mv = policy.choose_move(state, &state.get_legal_move_list(), random, evaluator);
+        history.make_move(mv);
+        counter += 1;
+    }
+}
+
+// an advantage of at least a man decides the game, anything less is a draw
+pub fn adjudicate<C: Checkers, E: Evaluator>(state: C, evaluator: &E) -> Option<Player> {
+    let score = evaluator.evaluate(&state);
+    if score >= evaluator.man_value() {
+        Some(state.get_turn())
+    } else if score <= -evaluator.man_value() {
+        Some(!state.get_turn())
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{arena, MctsConfig};
+    use crate::agent::evaluation::HandCrafted;
+    use crate::agent::mcts_hash::MctsHashAgent;
+    use crate::board::{fast, fen};
+    use rand::SeedableRng;
+
+    const POLICIES: [RolloutPolicy; 4] = [
+        RolloutPolicy::Uniform,
+        RolloutPolicy::Greedy,
+        RolloutPolicy::EpsilonGreedy { epsilon: 0.1 },
+        RolloutPolicy::Cutoff { plies: 20 },
+    ];
+
+    #[test]
+    fn test_rollout_blocked() {
+        let mut random = SmallRng::seed_from_u64(0);
+        let state = fast::BoardState::new(1 << 31, (1 << 27) | (1 << 23), 0, Player::Human);
+        for policy in POLICIES {
+            assert_eq!(rollout(state, policy, 500, &mut random, &HandCrafted::default()), Some(Player::Cpu));
+        }
+    }
+
+    #[test]
+    fn test_adjudicate() {
+        let evaluator = HandCrafted::default();
+        let state: fast::BoardState = fen::from_fen("W:W21,22:B5").unwrap();
+        assert_eq!(adjudicate(state, &evaluator), Some(Player::Cpu));
+        let state: fast::BoardState = fen::from_fen("W:W21:B5").unwrap();
+        assert_eq!(adjudicate(state, &evaluator), None);
+    }
+
+    // thinks the player to move is always a king behind
+    struct Pessimist;
+
+    impl Evaluator for Pessimist {
+        fn evaluate<C: Checkers>(&self, _: &C) -> i32 {
+            -160
+        }
+    }
+
+    #[test]
+    fn test_custom_evaluator() {
+        let mut random = SmallRng::seed_from_u64(0);
+        let state: fast::BoardState = fen::from_fen("W:W21:B5").unwrap();
+        assert_eq!(adjudicate(state, &Pessimist), Some(Player::Human));
+        // cut short right away, the rollout is decided by the evaluator alone
+        let policy = RolloutPolicy::Cutoff { plies: 0 };
+        assert_eq!(rollout(state, policy, 500, &mut random, &Pessimist), Some(Player::Human));
+    }
+
+    #[test]
+    fn test_choose_move() {
+        let mut random = SmallRng::seed_from_u64(0);
+        let evaluator = HandCrafted::default();
+
+        // the man on 26 can be crowned, which comes before anything else
+        let state: fast::BoardState = fen::from_fen("B:W22,K5:B26,14").unwrap();
+        let moves = state.get_legal_move_list();
+        for _ in 0..20 {
+            let mv = RolloutPolicy::Greedy.choose_move(state, &moves, &mut random, &evaluator);
+            assert_eq!(mv.from, (1, 4));
+        }
+
+        // without randomness, the move that keeps the most material
+        let state: fast::BoardState = fen::from_fen("B:W22:B14,15").unwrap();
+        let moves = state.get_legal_move_list();
+        let greedy = RolloutPolicy::EpsilonGreedy { epsilon: 0.0 };
+        for _ in 0..20 {
+            let mv = greedy.choose_move(state, &moves, &mut random, &evaluator);
+            let after = Checkers::make_move(&state, mv);
+            assert!(!can_capture(after) || after.get_turn() == state.get_turn());
+        }
+
+        // every policy only ever plays legal moves
+        let state = <fast::BoardState as Checkers>::default();
+        for policy in POLICIES {
+            let mv = policy.choose_move(state, &state.get_legal_move_list(), &mut random, &evaluator);
+            assert!(state.get_legal_move_list().contains(&mv));
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let config = MctsConfig::from_json(r#"{"rollout_policy": {"EpsilonGreedy": {"epsilon": 0.2}}}"#).unwrap();
+        assert_eq!(config.rollout_policy, RolloutPolicy::EpsilonGreedy { epsilon: 0.2 });
+        let config = MctsConfig::from_json(r#"{"rollout_policy": "Greedy"}"#).unwrap();
+        assert_eq!(config.rollout_policy, RolloutPolicy::Greedy);
+        assert!(MctsConfig::from_json(r#"{"rollout_policy": "Smart"}"#).is_err());
+    }
+
+    // a benchmark rather than a test, run it with `cargo test --release -- --ignored --nocapture`
+    // most games between agents this weak end in draws, so the scores are printed rather than checked
+    #[test]
+    #[ignore]
+    fn test_against_uniform() {
+        // the policies that need no evaluation on every move, the others are too slow to play whole games with here
+        let uniform_config = MctsConfig { iterations: 1_000, max_rollout_plies: 100, ..MctsConfig::default() };
+        for policy in [RolloutPolicy::Greedy, RolloutPolicy::Cutoff { plies: 20 }] {
+            let mut agent = MctsHashAgent::<fast::BoardState>::new(MctsConfig { rollout_policy: policy, ..uniform_config });
+            let mut uniform = MctsHashAgent::new(uniform_config);
+            println!("{:?} against uniform: {:?}", policy, arena::compare(&mut agent, &mut uniform, 40, 200));
+        }
+    }
+}
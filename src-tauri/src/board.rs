@@ -1,9 +1,18 @@
+use itertools::Itertools;
 use ts_rs::TS;
 
 pub mod fast;
 pub mod slow;
+pub mod history;
+pub mod notation;
+pub mod fen;
+pub mod zobrist;
+pub mod perft;
+pub mod move_list;
+pub mod tablebase;
 
 use super::public;
+use move_list::MoveList;
 
 #[derive(TS, Debug, PartialEq, Eq, Clone, Copy, Hash, serde::Deserialize, serde::Serialize)]
 pub enum Player {
@@ -19,6 +28,97 @@ pub struct Move {
     pub is_skip_move: bool,
 }
 
+// a complete turn, i.e. a simple move or an entire chain of captures
+#[derive(TS, Debug, PartialEq, Eq, Clone, Hash, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub struct CompoundMove {
+    pub from: (u8, u8),
+    // every square the piece lands on, the last one being its final destination
+    pub path: Vec<(u8, u8)>,
+    // the squares of the captured pieces in the order they are captured
+    pub captured: Vec<(u8, u8)>,
+}
+
+impl CompoundMove {
+    pub fn to(&self) -> (u8, u8) {
+        *self.path.last().unwrap()
+    }
+
+    pub fn is_capture(&self) -> bool {
+        !self.captured.is_empty()
+    }
+
+    // the single steps that make up this move, as understood by `Checkers::make_move`
+    pub fn steps(&self) -> impl Iterator<Item = Move> + '_ {
+        std::iter::once(self.from).chain(self.path.iter().copied()).tuple_windows().map(|(from, to)| Move {
+            from,
+            to,
+            is_skip_move: self.is_capture()
+        })
+    }
+
+    fn push(&mut self, mv: Move) {
+        if mv.is_skip_move {
+            self.captured.push(((mv.from.0 + mv.to.0) / 2, (mv.from.1 + mv.to.1) / 2));
+        }
+        self.path.push(mv.to);
+    }
+}
+
+impl From<Move> for CompoundMove {
+    fn from(value: Move) -> Self {
+        let mut mv = Self {
+            from: value.from,
+            path: vec![],
+            captured: vec![]
+        };
+        mv.push(value);
+        mv
+    }
+}
+
+#[derive(TS, Debug, PartialEq, Eq, Clone, Copy, Hash, serde::Deserialize, serde::Serialize)]
+pub enum DrawReason {
+    Repetition,
+    NoProgress,
+}
+
+#[derive(TS, Debug, PartialEq, Eq, Clone, Copy, Hash, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub enum GameOutcome {
+    Win(Player),
+    Draw(DrawReason),
+    Ongoing,
+}
+
+#[derive(TS, Debug, PartialEq, Eq, Clone, Copy, Hash, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub enum IllegalMove {
+    GameOver(GameOutcome),
+    // there is no piece on the square the move starts from
+    NoPiece,
+    // the piece belongs to the player that is not to move
+    WrongSide,
+    // the piece can not move to the given square
+    IllegalDestination,
+    // the player has to capture (or continue capturing) instead
+    CaptureAvailable,
+}
+
+impl std::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IllegalMove::GameOver(outcome) => write!(f, "the game is over ({:?})", outcome),
+            IllegalMove::NoPiece => write!(f, "there is no piece to move"),
+            IllegalMove::WrongSide => write!(f, "it is not that piece's turn"),
+            IllegalMove::IllegalDestination => write!(f, "the piece can not move there"),
+            IllegalMove::CaptureAvailable => write!(f, "a capture is mandatory"),
+        }
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
 impl std::ops::Not for Player {
     type Output = Self;
 
@@ -30,11 +130,256 @@ impl std::ops::Not for Player {
     }
 }
 
-pub trait Checkers: std::hash::Hash + PartialEq + Eq + Clone + Copy + From<public::BoardState> + Into<public::BoardState> {
+pub trait Checkers: std::fmt::Debug + std::hash::Hash + PartialEq + Eq + Clone + Copy + From<public::BoardState> + Into<public::BoardState> {
     fn default() -> Self;
-    fn is_game_over(&self) -> bool;
-    fn get_winner(&self) -> Option<Player>;
-    fn get_legal_moves(&self) -> Vec<Move>;
+    // only considers the board itself, draws by repetition or lack of progress are tracked by `history::GameHistory`
+    fn get_outcome(&self) -> GameOutcome;
+    // the legal moves without allocating, which is what searches should use
+    fn get_legal_move_list(&self) -> MoveList;
+    // the move has to be legal, backends may panic on moves they can not make (see `try_make_move`)
     fn make_move(&self, mv: Move) -> Self;
     fn get_turn(&self) -> Player;
+    // `None` for empty squares as well as for coordinates off the board
+    fn get_tile(&self, pos: (u8, u8)) -> Option<public::Tile>;
+    // a move is progress if it captures a piece or moves a man, i.e. if it can never be undone
+    // moves that can not be made (e.g. from off the board) are not progress
+    fn is_progress_move(&self, mv: Move) -> bool;
+    // the zobrist key of the position (see `zobrist`), maintained incrementally by `make_move`
+    // equal positions have equal keys, regardless of the backend
+    fn zobrist_hash(&self) -> u64;
+
+    fn get_legal_moves(&self) -> Vec<Move> {
+        self.get_legal_move_list().to_vec()
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.get_outcome() != GameOutcome::Ongoing
+    }
+
+    // lists every complete turn, following each capture until the turn passes to the opponent
+    fn get_legal_compound_moves(&self) -> Vec<CompoundMove> {
+        let mut moves = vec![];
+        for mv in self.get_legal_move_list().iter().copied() {
+            extend_compound_move(*self, CompoundMove::from(mv), mv, &mut moves);
+        }
+        moves
+    }
+
+    fn make_compound_move(&self, mv: &CompoundMove) -> Self {
+        mv.steps().fold(*self, |state, step| state.make_move(step))
+    }
+
+    // like `make_move`, but only for moves that are actually legal in this position
+    fn try_make_move(&self, mv: Move) -> Result<Self, IllegalMove> {
+        let outcome = self.get_outcome();
+        if outcome != GameOutcome::Ongoing {
+            return Err(IllegalMove::GameOver(outcome));
+        }
+
+        // moves come from the frontend, so they may point anywhere
+        if !is_on_board(mv.from) {
+            return Err(IllegalMove::NoPiece);
+        }
+        if !is_on_board(mv.to) {
+            return Err(IllegalMove::IllegalDestination);
+        }
+
+        match self.get_tile(mv.from) {
+            None => return Err(IllegalMove::NoPiece),
+            Some(tile) if tile.player != self.get_turn() => return Err(IllegalMove::WrongSide),
+            _ => ()
+        }
+
+        let legal_moves = self.get_legal_move_list();
+        // only the squares matter, `is_skip_move` is implied by them
+        if let Some(legal_move) = legal_moves.iter().find(|legal_move| legal_move.from == mv.from && legal_move.to == mv.to) {
+            return Ok(self.make_move(*legal_move));
+        }
+
+        if legal_moves.iter().any(|legal_move| legal_move.is_skip_move) {
+            Err(IllegalMove::CaptureAvailable)
+        } else {
+            Err(IllegalMove::IllegalDestination)
+        }
+    }
+
+    fn try_make_compound_move(&self, mv: &CompoundMove) -> Result<Self, IllegalMove> {
+        let next_state = mv.steps().try_fold(*self, |state, step| state.try_make_move(step))?;
+
+        // stopping in the middle of a capture chain is not a complete turn
+        if next_state.get_turn() == self.get_turn() {
+            return Err(IllegalMove::CaptureAvailable);
+        }
+        Ok(next_state)
+    }
+}
+
+pub fn is_on_board(pos: (u8, u8)) -> bool {
+    pos.0 < 8 && pos.1 < 8
+}
+
+fn extend_compound_move<C: Checkers>(state: C, partial: CompoundMove, last: Move, moves: &mut Vec<CompoundMove>) {
+    // the turn only stays with the same player while the capturing piece continues its chain
+    let next_state = state.make_move(last);
+    let continuations = match next_state.get_turn() == state.get_turn() {
+        true => next_state.get_legal_move_list(),
+        false => MoveList::new()
+    };
+
+    if continuations.is_empty() {
+        moves.push(partial);
+        return;
+    }
+
+    for mv in continuations.iter().copied() {
+        let mut extended = partial.clone();
+        extended.push(mv);
+        extend_compound_move(next_state, extended, mv, moves);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(player: Player) -> Option<public::Tile> {
+        Some(public::Tile { player, is_king: false })
+    }
+
+    // a human man that can capture twice, with a choice of direction for the second capture
+    fn double_capture() -> public::BoardState {
+        let mut tiles = [[None; 8]; 8];
+        tiles[6][1] = tile(Player::Human);
+        tiles[7][6] = tile(Player::Human);
+        tiles[5][2] = tile(Player::Cpu);
+        tiles[3][2] = tile(Player::Cpu);
+        tiles[3][4] = tile(Player::Cpu);
+        public::BoardState { tiles, turn: Player::Human, capturing: None }
+    }
+
+    fn check_capture_chain<C: Checkers>() {
+        let state = C::from(double_capture());
+        let state = state.make_move(Move { from: (6, 1), to: (4, 3), is_skip_move: true });
+        assert_eq!(state.get_turn(), Player::Human);
+
+        // only the capturing piece may continue, even though the other man could move
+        let mut moves = state.get_legal_moves();
+        moves.sort_by_key(|mv| mv.to);
+        assert_eq!(moves, vec![
+            Move { from: (4, 3), to: (2, 1), is_skip_move: true },
+            Move { from: (4, 3), to: (2, 5), is_skip_move: true },
+        ]);
+        assert_eq!(Into::<public::BoardState>::into(state).capturing, Some((4, 3)));
+
+        let state = state.make_move(moves[0]);
+        assert_eq!(state.get_turn(), Player::Cpu);
+        assert_eq!(Into::<public::BoardState>::into(state).capturing, None);
+    }
+
+    fn check_other_piece_can_not_continue<C: Checkers>() {
+        let mut tiles = [[None; 8]; 8];
+        tiles[6][1] = tile(Player::Human);
+        tiles[6][5] = tile(Player::Human);
+        tiles[5][2] = tile(Player::Cpu);
+        tiles[5][6] = tile(Player::Cpu);
+        let state = C::from(public::BoardState { tiles, turn: Player::Human, capturing: None });
+
+        let state = state.make_move(Move { from: (6, 1), to: (4, 3), is_skip_move: true });
+        assert_eq!(state.get_turn(), Player::Cpu);
+    }
+
+    fn check_crowning_ends_turn<C: Checkers>() {
+        let mut tiles = [[None; 8]; 8];
+        tiles[2][1] = tile(Player::Human);
+        tiles[1][2] = tile(Player::Cpu);
+        tiles[1][4] = tile(Player::Cpu);
+        let state = C::from(public::BoardState { tiles, turn: Player::Human, capturing: None });
+
+        // as a king, the piece could go on to capture the man on (1, 4)
+        let state = state.make_move(Move { from: (2, 1), to: (0, 3), is_skip_move: true });
+        assert_eq!(state.get_turn(), Player::Cpu);
+        let public_state: public::BoardState = state.into();
+        assert_eq!(public_state.tiles[0][3], Some(public::Tile { player: Player::Human, is_king: true }));
+        assert_eq!(public_state.tiles[1][4], tile(Player::Cpu));
+    }
+
+    fn check_compound_moves<C: Checkers>() {
+        let state = C::from(double_capture());
+        let mut moves = state.get_legal_compound_moves();
+        moves.sort_by_key(|mv| mv.path.clone());
+        assert_eq!(moves, vec![
+            CompoundMove { from: (6, 1), path: vec![(4, 3), (2, 1)], captured: vec![(5, 2), (3, 2)] },
+            CompoundMove { from: (6, 1), path: vec![(4, 3), (2, 5)], captured: vec![(5, 2), (3, 4)] },
+        ]);
+
+        let next_state: public::BoardState = state.make_compound_move(&moves[0]).into();
+        assert_eq!(next_state.turn, Player::Cpu);
+        assert_eq!(next_state.tiles[2][1], tile(Player::Human));
+        assert_eq!(next_state.tiles[5][2], None);
+        assert_eq!(next_state.tiles[3][2], None);
+        assert_eq!(next_state.tiles[3][4], tile(Player::Cpu));
+    }
+
+    #[test]
+    fn test_compound_moves() {
+        check_compound_moves::<slow::BoardState>();
+        check_compound_moves::<fast::BoardState>();
+    }
+
+    #[test]
+    fn test_capture_chain() {
+        check_capture_chain::<slow::BoardState>();
+        check_capture_chain::<fast::BoardState>();
+        check_other_piece_can_not_continue::<slow::BoardState>();
+        check_other_piece_can_not_continue::<fast::BoardState>();
+        check_crowning_ends_turn::<slow::BoardState>();
+        check_crowning_ends_turn::<fast::BoardState>();
+    }
+
+    fn check_try_make_move<C: Checkers>() {
+        let state = C::default();
+        assert!(state.try_make_move(Move { from: (5, 0), to: (4, 1), is_skip_move: false }).is_ok());
+        assert_eq!(state.try_make_move(Move { from: (4, 1), to: (3, 2), is_skip_move: false }), Err(IllegalMove::NoPiece));
+        assert_eq!(state.try_make_move(Move { from: (2, 1), to: (3, 2), is_skip_move: false }), Err(IllegalMove::WrongSide));
+        assert_eq!(state.try_make_move(Move { from: (5, 0), to: (3, 2), is_skip_move: true }), Err(IllegalMove::IllegalDestination));
+        assert_eq!(state.try_make_move(Move { from: (6, 1), to: (5, 2), is_skip_move: false }), Err(IllegalMove::IllegalDestination));
+        assert_eq!(state.try_make_move(Move { from: (8, 255), to: (7, 0), is_skip_move: false }), Err(IllegalMove::NoPiece));
+        assert_eq!(state.try_make_move(Move { from: (5, 0), to: (4, 255), is_skip_move: false }), Err(IllegalMove::IllegalDestination));
+        assert_eq!(state.get_tile((255, 255)), None);
+
+        let state = C::from(double_capture());
+        assert_eq!(state.try_make_move(Move { from: (7, 6), to: (6, 5), is_skip_move: false }), Err(IllegalMove::CaptureAvailable));
+        // a move is matched by its squares alone
+        let next_state = state.try_make_move(Move { from: (6, 1), to: (4, 3), is_skip_move: false }).unwrap();
+        assert_eq!(next_state, state.make_move(Move { from: (6, 1), to: (4, 3), is_skip_move: true }));
+
+        let partial_move = CompoundMove { from: (6, 1), path: vec![(4, 3)], captured: vec![(5, 2)] };
+        assert_eq!(state.try_make_compound_move(&partial_move), Err(IllegalMove::CaptureAvailable));
+        let full_move = CompoundMove { from: (6, 1), path: vec![(4, 3), (2, 5)], captured: vec![(5, 2), (3, 4)] };
+        assert_eq!(state.try_make_compound_move(&full_move), Ok(state.make_compound_move(&full_move)));
+
+        let mut tiles = [[None; 8]; 8];
+        tiles[0][1] = tile(Player::Cpu);
+        let state = C::from(public::BoardState { tiles, turn: Player::Human, capturing: None });
+        assert_eq!(
+            state.try_make_move(Move { from: (0, 1), to: (1, 2), is_skip_move: false }),
+            Err(IllegalMove::GameOver(GameOutcome::Win(Player::Cpu)))
+        );
+    }
+
+    #[test]
+    fn test_try_make_move() {
+        check_try_make_move::<slow::BoardState>();
+        check_try_make_move::<fast::BoardState>();
+    }
+
+    #[test]
+    fn test_simple_compound_moves() {
+        let state = slow::BoardState::default();
+        let moves = state.get_legal_compound_moves();
+        assert_eq!(moves.len(), 7);
+        assert!(moves.iter().all(|mv| mv.path.len() == 1 && !mv.is_capture()));
+        assert_eq!(moves[0].steps().collect::<Vec<_>>(), vec![state.get_legal_moves()[0]]);
+    }
 }
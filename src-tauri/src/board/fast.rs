@@ -10,7 +10,9 @@
 //    -------HUMAN------
 // "dead" positions: [-1, 08, 17, 26, 35]
 // I found this blog post which does sort of the same thing: https://3dkingdoms.com/checkers/bitboards.htm
-use super::{Checkers, Player};
+use super::{zobrist, Checkers, GameOutcome, IllegalMove, Player};
+use super::move_list::MoveList;
+use super::super::public;
 
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -40,22 +42,31 @@ const fn tiles_for_player(player: Player) -> u64 {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct BoardState {
-    pub tiles_human: u64,
-    pub tiles_cpu: u64,
-    pub kings: u64,
-    pub turn: Player
+    tiles_human: u64,
+    tiles_cpu: u64,
+    kings: u64,
+    turn: Player,
+    // the piece that has to continue capturing (0 if the turn is not in the middle of a capture chain)
+    capturing: u64,
+    // the zobrist key of the fields above, which is why they can only be changed through `new` and `make_move`
+    hash: u64
 }
 
-impl Default for BoardState {
-    fn default() -> Self {
-        BoardState {
-            tiles_human: tiles_for_player(Player::Human),
-            tiles_cpu: tiles_for_player(Player::Cpu),
-            kings: 0,
-            turn: Player::Human
-        }
+// the key is derived from the position, so it is left out of comparisons and is all that needs hashing
+impl PartialEq for BoardState {
+    fn eq(&self, other: &Self) -> bool {
+        (self.tiles_human, self.tiles_cpu, self.kings, self.turn, self.capturing)
+            == (other.tiles_human, other.tiles_cpu, other.kings, other.turn, other.capturing)
+    }
+}
+
+impl Eq for BoardState {}
+
+impl std::hash::Hash for BoardState {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
     }
 }
 
@@ -76,8 +87,8 @@ impl BitSet for u64 {
     }
 
     fn get_at_position(&self, position: usize) -> Option<bool> {
-        // if position is illegal, return None
-        if !LEGAL_TILES_MASK & (1 << position) != 0 {
+        // if position is illegal (or wrapped around the edge of the board), return None
+        if position >= 36 || !LEGAL_TILES_MASK & (1 << position) != 0 {
             return None;
         }
         Some(self & (1 << position) != 0)
@@ -101,26 +112,259 @@ pub const fn delta_for_dir(dir: &Direction) -> i8 {
     }
 }
 
-pub const fn dir_for_delta(delta: i8) -> Direction {
+// in the order of `Direction`, so moves come out sorted
+pub const DIRECTIONS: [Direction; 4] = [Direction::UpLeft, Direction::UpRight, Direction::DownLeft, Direction::DownRight];
+
+// the directions men of each player move in, as a table indexed by `Player`
+const FORWARD: [[bool; 4]; 2] = [
+    [true, true, false, false],
+    [false, false, true, true],
+];
+
+// moving a whole bitboard one step in a direction, squares that leave the board end up on the dead squares
+// (or are shifted out entirely), so masking with the vacant squares takes care of the edges
+const fn shift(bits: u64, delta: i8) -> u64 {
+    if delta > 0 {
+        bits << delta
+    } else {
+        bits >> -delta
+    }
+}
+
+// iterates over the set bits, lowest first
+fn bits(mut bits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let position = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Some(position)
+    })
+}
+
+// `None` for anything that is not a step or jump to a neighbouring diagonal square
+pub const fn dir_for_delta(delta: i8) -> Option<Direction> {
     match delta {
-        -5 => Direction::UpLeft,
-        -4 => Direction::UpRight,
-        4 => Direction::DownLeft,
-        5 => Direction::DownRight,
-        -10 => Direction::UpLeft,
-        -8 => Direction::UpRight,
-        8 => Direction::DownLeft,
-        10 => Direction::DownRight,
-        _ => panic!("Invalid delta")
+        -5 | -10 => Some(Direction::UpLeft),
+        -4 | -8 => Some(Direction::UpRight),
+        4 | 8 => Some(Direction::DownLeft),
+        5 | 10 => Some(Direction::DownRight),
+        _ => None
+    }
+}
+
+// the ghost-square layout packs two board rows into 9 bits (the 9th being a "dead" square)
+pub const fn position_to_coords(position: usize) -> (u8, u8) {
+    let row = 2 * (position / 9) + (position % 9) / 4;
+    let col = 2 * (position % 9 % 4) + (row + 1) % 2;
+    (row as u8, col as u8)
+}
+
+pub const fn coords_to_position(coords: (u8, u8)) -> usize {
+    let (row, col) = (coords.0 as usize, coords.1 as usize);
+    9 * (row / 2) + 4 * (row % 2) + col / 2
+}
+
+fn piece_key(position: usize, player: Player, is_king: bool) -> u64 {
+    zobrist::piece_key(position_to_coords(position), player, is_king)
+}
+
+// conversion logic
+impl From<public::BoardState> for BoardState {
+    fn from(value: public::BoardState) -> Self {
+        let mut state = BoardState {
+            tiles_human: 0,
+            tiles_cpu: 0,
+            kings: 0,
+            turn: value.turn,
+            capturing: value.capturing.map_or(0, |coords| 1 << coords_to_position(coords)),
+            hash: zobrist::hash_position(&value)
+        };
+
+        for (row, tiles) in value.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                // pieces on light squares can not be represented (and can never occur in a real game)
+                let Some(tile) = tile else { continue };
+                if (row + col) % 2 == 0 {
+                    continue;
+                }
+
+                let position = coords_to_position((row as u8, col as u8));
+                match tile.player {
+                    Player::Human => state.tiles_human = state.tiles_human.set_at_position(position, true),
+                    Player::Cpu => state.tiles_cpu = state.tiles_cpu.set_at_position(position, true),
+                }
+                state.kings = state.kings.set_at_position(position, tile.is_king);
+            }
+        }
+
+        state
+    }
+}
+
+impl From<BoardState> for public::BoardState {
+    fn from(value: BoardState) -> Self {
+        let mut tiles = [[None; 8]; 8];
+
+        for position in LEGAL_TILES_MASK.iter_ones() {
+            let (row, col) = position_to_coords(position);
+            tiles[row as usize][col as usize] = value.get_tile((row, col));
+        }
+
+        public::BoardState {
+            tiles,
+            turn: value.turn,
+            capturing: value.capturing.iter_ones().next().map(position_to_coords)
+        }
+    }
+}
+
+impl From<Move> for super::Move {
+    fn from(value: Move) -> Self {
+        let distance = if value.skip { 2 } else { 1 };
+        let to = (value.position as i8 + distance * delta_for_dir(&value.direction)) as usize;
+        Self {
+            from: position_to_coords(value.position),
+            to: position_to_coords(to),
+            is_skip_move: value.skip
+        }
+    }
+}
+
+// only steps and jumps along a diagonal of the board can be represented, anything else is rejected
+impl TryFrom<super::Move> for Move {
+    type Error = IllegalMove;
+
+    fn try_from(value: super::Move) -> Result<Self, Self::Error> {
+        let is_dark_square = |(row, col): (u8, u8)| row < 8 && col < 8 && (row + col) % 2 == 1;
+        let distance = (value.from.0.abs_diff(value.to.0), value.from.1.abs_diff(value.to.1));
+        if !is_dark_square(value.from) || !is_dark_square(value.to) || !matches!(distance, (1, 1) | (2, 2)) {
+            return Err(IllegalMove::IllegalDestination);
+        }
+
+        let position = coords_to_position(value.from);
+        let delta = coords_to_position(value.to) as i8 - position as i8;
+        Ok(Self {
+            position,
+            direction: dir_for_delta(delta).ok_or(IllegalMove::IllegalDestination)?,
+            skip: value.is_skip_move
+        })
+    }
+}
+
+impl Default for BoardState {
+    fn default() -> Self {
+        <Self as Checkers>::default()
+    }
+}
+
+impl Checkers for BoardState {
+    fn default() -> Self {
+        BoardState::new(tiles_for_player(Player::Human), tiles_for_player(Player::Cpu), 0, Player::Human)
+    }
+
+    fn get_outcome(&self) -> GameOutcome {
+        match BoardState::get_winner(self) {
+            Some(player) => GameOutcome::Win(player),
+            None => GameOutcome::Ongoing
+        }
+    }
+
+    fn get_legal_move_list(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        self.for_each_legal_move(|mv| moves.push(mv.into()));
+        moves
+    }
+
+    fn make_move(&self, mv: super::Move) -> Self {
+        // like on every backend, the move has to be legal (see `try_make_move`)
+        // panics on moves that are neither a step nor a jump between two squares of the board
+        let mv = Move::try_from(mv).expect("the move is not a step or jump");
+        BoardState::make_move(self, mv)
+    }
+
+    fn get_turn(&self) -> Player {
+        self.turn
+    }
+
+    fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn get_tile(&self, pos: (u8, u8)) -> Option<public::Tile> {
+        // light squares can never hold a piece
+        if !super::is_on_board(pos) || (pos.0 + pos.1) % 2 == 0 {
+            return None;
+        }
+
+        let position = coords_to_position(pos);
+        let player = if self.tiles_human.get_at_position(position) == Some(true) {
+            Player::Human
+        } else if self.tiles_cpu.get_at_position(position) == Some(true) {
+            Player::Cpu
+        } else {
+            return None;
+        };
+
+        Some(public::Tile {
+            player,
+            is_king: self.kings.get_at_position(position) == Some(true)
+        })
+    }
+
+    fn is_progress_move(&self, mv: super::Move) -> bool {
+        match Move::try_from(mv) {
+            Ok(mv) => mv.skip || self.kings.get_at_position(mv.position) != Some(true),
+            Err(_) => false
+        }
     }
 }
 
 impl BoardState {
+    pub fn new(tiles_human: u64, tiles_cpu: u64, kings: u64, turn: Player) -> Self {
+        BoardState { tiles_human, tiles_cpu, kings, turn, capturing: 0, hash: 0 }.rehash()
+    }
+
+    pub fn tiles_human(&self) -> u64 {
+        self.tiles_human
+    }
+
+    pub fn tiles_cpu(&self) -> u64 {
+        self.tiles_cpu
+    }
+
+    pub fn kings(&self) -> u64 {
+        self.kings
+    }
+
+    pub fn capturing(&self) -> u64 {
+        self.capturing
+    }
+
+    // recomputes the zobrist key from scratch
+    fn rehash(self) -> Self {
+        let mut hash = zobrist::turn_key(self.turn);
+        for position in self.tiles_human.iter_ones() {
+            hash ^= piece_key(position, Player::Human, self.kings.get_at_position(position) == Some(true));
+        }
+        for position in self.tiles_cpu.iter_ones() {
+            hash ^= piece_key(position, Player::Cpu, self.kings.get_at_position(position) == Some(true));
+        }
+        for position in self.capturing.iter_ones() {
+            hash ^= zobrist::capturing_key(position_to_coords(position));
+        }
+        BoardState { hash, ..self }
+    }
+
     pub fn get_winner(&self) -> Option<Player> {
         if self.tiles_human == 0 {
             Some(Player::Cpu)
         } else if self.tiles_cpu == 0 {
             Some(Player::Human)
+        } else if !self.can_move() {
+            // a player that can not move loses
+            Some(!self.turn)
         } else {
             None
         }
@@ -130,14 +374,92 @@ impl BoardState {
         self.get_winner().is_some()
     }
 
+    fn own_and_enemy_tiles(&self) -> (u64, u64) {
+        match self.turn {
+            Player::Human => (self.tiles_human, self.tiles_cpu),
+            Player::Cpu => (self.tiles_cpu, self.tiles_human)
+        }
+    }
+
+    // the pieces that may move in each direction (in the middle of a capture chain, only the capturing piece)
+    fn movers(&self) -> [u64; 4] {
+        let (own_tiles, _) = self.own_and_enemy_tiles();
+        let own_tiles = match self.capturing {
+            0 => own_tiles,
+            capturing => own_tiles & capturing
+        };
+
+        let forward = FORWARD[self.turn as usize];
+        [0, 1, 2, 3].map(|i| if forward[i] { own_tiles } else { own_tiles & self.kings })
+    }
+
+    // the pieces that can capture in each direction
+    fn jumpers(&self) -> [u64; 4] {
+        let (_, enemy_tiles) = self.own_and_enemy_tiles();
+        let vacant = !(self.tiles_human | self.tiles_cpu) & LEGAL_TILES_MASK;
+        let movers = self.movers();
+        [0, 1, 2, 3].map(|i| {
+            let delta = delta_for_dir(&DIRECTIONS[i]);
+            movers[i] & shift(enemy_tiles & shift(vacant, -delta), -delta)
+        })
+    }
+
+    // the pieces that can make a simple move in each direction
+    fn steppers(&self) -> [u64; 4] {
+        let vacant = !(self.tiles_human | self.tiles_cpu) & LEGAL_TILES_MASK;
+        let movers = self.movers();
+        [0, 1, 2, 3].map(|i| movers[i] & shift(vacant, -delta_for_dir(&DIRECTIONS[i])))
+    }
+
+    fn can_capture(&self) -> bool {
+        self.jumpers().iter().any(|jumpers| *jumpers != 0)
+    }
+
+    fn can_move(&self) -> bool {
+        self.can_capture() || (self.capturing == 0 && self.steppers().iter().any(|steppers| *steppers != 0))
+    }
+
+    // computes the moves of all pieces at once with shifted bitboards, captures being mandatory
+    pub fn for_each_legal_move(&self, mut f: impl FnMut(Move)) {
+        let jumpers = self.jumpers();
+        let (sources, skip) = if self.capturing != 0 || jumpers.iter().any(|jumpers| *jumpers != 0) {
+            (jumpers, true)
+        } else {
+            (self.steppers(), false)
+        };
+
+        for position in bits(sources.iter().fold(0, |all, sources| all | sources)) {
+            for (i, direction) in DIRECTIONS.iter().enumerate() {
+                if sources[i] & (1 << position) != 0 {
+                    f(Move { position, direction: *direction, skip });
+                }
+            }
+        }
+    }
+
     pub fn get_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(16);
+        self.for_each_legal_move(|mv| moves.push(mv));
+        moves
+    }
+
+    // the original generator, which walks every piece and direction on its own
+    // it is kept as a reference for the shift-based generator in tests and benchmarks
+    #[doc(hidden)]
+    pub fn get_legal_moves_per_piece(&self) -> Vec<Move> {
         // let vacant_tiles = !(self.tiles_human | self.tiles_cpu) & LEGAL_TILES_MASK;
         let (own_tiles, enemy_tiles) = match self.turn {
             Player::Human => (self.tiles_human, self.tiles_cpu),
             Player::Cpu => (self.tiles_cpu, self.tiles_human)
         };
 
-        let moves = own_tiles.iter_ones().flat_map(|pos| {
+        // in the middle of a capture chain, only the capturing piece may move
+        let movers = match self.capturing {
+            0 => own_tiles,
+            capturing => own_tiles & capturing
+        };
+
+        let moves = movers.iter_ones().flat_map(|pos| {
             let is_king = self.kings.get_at_position(pos).unwrap();
 
             let directions = if is_king {
@@ -167,7 +489,7 @@ impl BoardState {
 
         // if there is a move with skip, only return the moves with skip
         // if there is no move with skip, return all
-        if moves.iter().any(|mv| mv.skip) {
+        if self.capturing != 0 || moves.iter().any(|mv| mv.skip) {
             moves.into_iter().filter(|mv| mv.skip).collect()
         } else {
             moves
@@ -183,33 +505,46 @@ impl BoardState {
         let is_king = self.kings.get_at_position(mv.position).unwrap();
         let next_pos = (mv.position as i8 + delta_for_dir(&mv.direction)) as usize;
         
+        let landing_pos = match mv.skip {
+            true => (next_pos as i8 + delta_for_dir(&mv.direction)) as usize,
+            false => next_pos
+        };
+        let is_promoted = !is_king && !(4..31).contains(&landing_pos);
+
+        let mut hash = self.hash ^ piece_key(mv.position, self.turn, is_king) ^ piece_key(landing_pos, self.turn, is_king || is_promoted);
+        if mv.skip {
+            hash ^= piece_key(next_pos, !self.turn, self.kings.get_at_position(next_pos) == Some(true));
+        }
+        for position in self.capturing.iter_ones() {
+            hash ^= zobrist::capturing_key(position_to_coords(position));
+        }
+
         let (new_own_tiles, new_enemy_tiles, new_kings) = match mv.skip {
             true => {
-                let next_next_pos = (next_pos as i8 + delta_for_dir(&mv.direction)) as usize;
                 let new_enemy_tiles = enemy_tiles.set_at_position(next_pos, false);
-                let new_own_tiles = own_tiles.set_at_position(next_next_pos, true).set_at_position(mv.position, false);
+                let new_own_tiles = own_tiles.set_at_position(landing_pos, true).set_at_position(mv.position, false);
 
                 let mut new_kings = self.kings.set_at_position(next_pos, false);
                 if is_king {
-                    new_kings = self.kings.set_at_position(mv.position, false).set_at_position(next_next_pos, true);
+                    new_kings = new_kings.set_at_position(mv.position, false).set_at_position(landing_pos, true);
                 }
 
-                if next_next_pos < 4 || next_next_pos >= 31 {
-                    new_kings = new_kings.set_at_position(next_next_pos, true);
+                if is_promoted {
+                    new_kings = new_kings.set_at_position(landing_pos, true);
                 }
 
                 (new_own_tiles, new_enemy_tiles, new_kings)
             },
             false => {
-                let new_own_tiles = own_tiles.set_at_position(mv.position, false).set_at_position(next_pos, true);
+                let new_own_tiles = own_tiles.set_at_position(mv.position, false).set_at_position(landing_pos, true);
                 let mut new_kings = self.kings;
 
                 if is_king {
-                    new_kings = new_kings.set_at_position(mv.position, false).set_at_position(next_pos, true);
+                    new_kings = new_kings.set_at_position(mv.position, false).set_at_position(landing_pos, true);
                 }
                 
-                if next_pos < 4 || next_pos >= 31 {
-                    new_kings = new_kings.set_at_position(next_pos, true);
+                if is_promoted {
+                    new_kings = new_kings.set_at_position(landing_pos, true);
                 }
 
                 (new_own_tiles, enemy_tiles, new_kings)
@@ -217,7 +552,7 @@ impl BoardState {
         };
 
 
-        let mut new_state = Self {
+        let new_state = Self {
             tiles_human: match self.turn {
                 Player::Human => new_own_tiles,
                 Player::Cpu => new_enemy_tiles,
@@ -227,14 +562,22 @@ impl BoardState {
                 Player::Cpu => new_own_tiles,
             },
             kings: new_kings,
-            turn: self.turn
+            turn: !self.turn,
+            capturing: 0,
+            hash: hash ^ zobrist::TURN_KEY
         };
 
-        // if there are still more skip moves, keep the turn
-        if mv.skip && new_state.get_legal_moves().iter().any(|mv| mv.skip) {
-            new_state.turn = self.turn;
-        } else {
-            new_state.turn = !self.turn;
+        // the capturing piece keeps the turn as long as it can capture again (unless it was just crowned)
+        if mv.skip && !is_promoted {
+            let continuing_state = Self {
+                turn: self.turn,
+                capturing: 1 << landing_pos,
+                hash: hash ^ zobrist::capturing_key(position_to_coords(landing_pos)),
+                ..new_state
+            };
+            if continuing_state.can_capture() {
+                return continuing_state;
+            }
         }
 
         new_state
@@ -246,12 +589,14 @@ impl BoardState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::{fen, slow};
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::SmallRng;
+    use rand::seq::SliceRandom;
 
     #[test]
     fn test_skip_moves() {
-        let mut state = BoardState::default();
-        state.tiles_human = 1 << 24;
-        state.tiles_cpu = 1 << 20;
+        let state = BoardState::new(1 << 24, 1 << 20, 0, Player::Human);
         // should skip like 24 -> 20 -> 16
         // additionally, the skip is mandatory, which means there is only one legal move
         let legal_moves = state.get_legal_moves();
@@ -260,7 +605,7 @@ mod tests {
 
     #[test]
     fn test_moves() {
-        let mut state = BoardState::default();
+        let state = <BoardState as Checkers>::default();
         let mut legal_moves = state.get_legal_moves();
         legal_moves.sort();
         let mut target_moves = vec![
@@ -275,7 +620,7 @@ mod tests {
         target_moves.sort();
         assert_eq!(legal_moves, target_moves);
 
-        state.turn = Player::Cpu;
+        let state = BoardState::new(state.tiles_human, state.tiles_cpu, state.kings, Player::Cpu);
         legal_moves = state.get_legal_moves();
         legal_moves.sort();
         target_moves = vec![
@@ -293,7 +638,7 @@ mod tests {
 
     #[test]
     fn test_make_move() {
-        let mut state = BoardState::default();
+        let mut state = <BoardState as Checkers>::default();
         state = state.make_move(Move { position: 22, direction: Direction::UpRight, skip: false });
         state = state.make_move(Move { position: 12, direction: Direction::DownLeft, skip: false });
         
@@ -315,6 +660,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_blocked_player_loses() {
+        // the human man in the corner can neither move nor capture
+        let state = BoardState::new(1 << 31, (1 << 27) | (1 << 23), 0, Player::Human);
+        assert!(state.get_legal_moves().is_empty());
+        assert_eq!(state.get_winner(), Some(Player::Cpu));
+        assert_eq!(Checkers::get_outcome(&state), GameOutcome::Win(Player::Cpu));
+
+        // a king is blocked in the corner just the same
+        let state = BoardState::new(1 << 31, (1 << 27) | (1 << 23), 1 << 31, Player::Human);
+        assert_eq!(state.get_winner(), Some(Player::Cpu));
+
+        let state = BoardState::new(1 << 31, (1 << 27) | (1 << 23), 1 << 31, Player::Cpu);
+        assert_eq!(state.get_winner(), None);
+    }
+
     #[test]
     fn test_bitsets() {
         assert_eq!("0000000000000000000000000000011111111011111111011111111011111111", format!("{:064b}", LEGAL_TILES_MASK));
@@ -324,4 +685,256 @@ mod tests {
 
         assert_eq!(valid_indices, LEGAL_TILES_MASK.iter_ones().collect::<Vec<usize>>());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_coords_conversion() {
+        for position in LEGAL_TILES_MASK.iter_ones() {
+            let (row, col) = position_to_coords(position);
+            assert_eq!((row + col) % 2, 1);
+            assert_eq!(coords_to_position((row, col)), position);
+        }
+        assert_eq!(position_to_coords(0), (0, 1));
+        assert_eq!(position_to_coords(4), (1, 0));
+        assert_eq!(position_to_coords(34), (7, 6));
+    }
+
+    #[test]
+    fn test_public_conversion() {
+        let state = <BoardState as Checkers>::default();
+        let public_state: public::BoardState = state.into();
+        let slow_state: public::BoardState = super::super::slow::BoardState::default().into();
+        assert_eq!(public_state, slow_state);
+        assert_eq!(BoardState::from(public_state), state);
+    }
+
+    #[test]
+    fn test_move_conversion() {
+        let state = <BoardState as Checkers>::default();
+        for mv in state.get_legal_moves() {
+            let public_move: super::super::Move = mv.into();
+            assert_eq!(Move::try_from(public_move), Ok(mv));
+        }
+
+        let mv = Move { position: 24, direction: Direction::UpRight, skip: true };
+        let public_move: super::super::Move = mv.into();
+        assert_eq!(public_move, super::super::Move { from: (5, 4), to: (3, 6), is_skip_move: true });
+
+        // off the board, not along a diagonal, or too far
+        for (from, to) in [((7, 0), (8, 1)), ((5, 0), (5, 2)), ((5, 0), (2, 3)), ((0, 7), (3, 0))] {
+            let public_move = super::super::Move { from, to, is_skip_move: false };
+            assert_eq!(Move::try_from(public_move), Err(IllegalMove::IllegalDestination));
+            assert!(!Checkers::is_progress_move(&state, public_move));
+        }
+    }
+
+    // walks the game tree with single steps, comparing the generators in every position
+    fn check_generators(state: BoardState, depth: usize) {
+        let mut per_piece_moves = state.get_legal_moves_per_piece();
+        per_piece_moves.sort();
+        assert_eq!(state.get_legal_moves(), per_piece_moves, "{:?}", state);
+        assert_eq!(state.can_move(), !per_piece_moves.is_empty());
+
+        if depth > 0 {
+            for mv in per_piece_moves {
+                check_generators(state.make_move(mv), depth - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_generator() {
+        check_generators(<BoardState as Checkers>::default(), 6);
+
+        // random positions with plenty of kings, to cover the backwards directions and the edges
+        let mut random = rand::rngs::SmallRng::seed_from_u64(14);
+        for _ in 0..200 {
+            let (mut tiles_human, mut tiles_cpu) = (0, 0);
+            for position in LEGAL_TILES_MASK.iter_ones() {
+                match random.gen_range(0..4) {
+                    0 => tiles_human |= 1 << position,
+                    1 => tiles_cpu |= 1 << position,
+                    _ => ()
+                }
+            }
+            let kings = (tiles_human | tiles_cpu) & random.gen::<u64>();
+            let turn = if random.gen_bool(0.5) { Player::Human } else { Player::Cpu };
+            check_generators(BoardState::new(tiles_human, tiles_cpu, kings, turn), 3);
+        }
+    }
+
+    #[test]
+    fn test_capture_king() {
+        let mut state = BoardState::new(1 << 24, 1 << 20, (1 << 24) | (1 << 20), Player::Human);
+        state = state.make_move(Move { position: 24, direction: Direction::UpRight, skip: true });
+        assert_eq!(state.kings, 1 << 16);
+    }
+
+    // differential tests between the two backends: random positions and random games are played on both boards at
+    // the same time, and any disagreement is shrunk to a minimal position (printed as FEN) before failing
+    fn sorted_moves<C: Checkers>(state: C) -> Vec<super::super::Move> {
+        let mut moves = state.get_legal_moves();
+        moves.sort_by_key(|mv| (mv.from, mv.to));
+        moves
+    }
+
+    // describes the first difference between the states, which are supposed to be the same position
+    fn compare(slow_state: slow::BoardState, fast_state: BoardState) -> Option<String> {
+        let (slow_public, fast_public): (public::BoardState, public::BoardState) = (slow_state.into(), fast_state.into());
+        if slow_public != fast_public {
+            // covers the pieces (including promotions), the turn and the capturing piece
+            return Some(format!("positions differ: {} vs {}", describe(&slow_public), describe(&fast_public)));
+        }
+        if slow_state.get_outcome() != fast_state.get_outcome() {
+            return Some(format!("outcomes differ: {:?} vs {:?}", slow_state.get_outcome(), fast_state.get_outcome()));
+        }
+        if slow_state.zobrist_hash() != fast_state.zobrist_hash() {
+            return Some("zobrist hashes differ".to_string());
+        }
+        None
+    }
+
+    // checks the position itself and every position one move away from it
+    fn find_mismatch(position: &public::BoardState) -> Option<String> {
+        let slow_state = slow::BoardState::from(position.clone());
+        let fast_state = BoardState::from(position.clone());
+        if let Some(mismatch) = compare(slow_state, fast_state) {
+            return Some(mismatch);
+        }
+
+        let moves = sorted_moves(slow_state);
+        if moves != sorted_moves(fast_state) {
+            return Some(format!("legal moves differ: {:?} vs {:?}", moves, sorted_moves(fast_state)));
+        }
+
+        moves.into_iter().find_map(|mv| {
+            let mismatch = compare(slow_state.make_move(mv), Checkers::make_move(&fast_state, mv))?;
+            Some(format!("after {:?}: {}", mv, mismatch))
+        })
+    }
+
+    fn describe(position: &public::BoardState) -> String {
+        format!("{} (capturing {:?})", fen::to_fen(position), position.capturing)
+    }
+
+    // every position that is one step simpler: a piece removed, or a king turned back into a man
+    fn simplifications(position: &public::BoardState) -> Vec<public::BoardState> {
+        let mut simplified = vec![];
+        for row in 0..8 {
+            for col in 0..8 {
+                let Some(tile) = position.tiles[row][col] else { continue };
+                // the capturing piece is what makes the position special
+                if position.capturing == Some((row as u8, col as u8)) {
+                    continue;
+                }
+
+                let mut removed = position.clone();
+                removed.tiles[row][col] = None;
+                simplified.push(removed);
+
+                if tile.is_king {
+                    let mut demoted = position.clone();
+                    demoted.tiles[row][col] = Some(public::Tile { is_king: false, ..tile });
+                    if demoted.validate().is_ok() {
+                        simplified.push(demoted);
+                    }
+                }
+            }
+        }
+        simplified
+    }
+
+    // greedily simplifies the position for as long as it keeps failing
+    fn shrink(position: &public::BoardState, fails: impl Fn(&public::BoardState) -> bool) -> public::BoardState {
+        let mut position = position.clone();
+        while let Some(simpler) = simplifications(&position).into_iter().find(|simpler| fails(simpler)) {
+            position = simpler;
+        }
+        position
+    }
+
+    fn check_position(position: &public::BoardState) {
+        let Some(mismatch) = find_mismatch(position) else { return };
+        let minimal = shrink(position, |position| find_mismatch(position).is_some());
+        panic!(
+            "{} in {}\nminimal position: {} ({})",
+            mismatch,
+            describe(position),
+            describe(&minimal),
+            find_mismatch(&minimal).unwrap()
+        );
+    }
+
+    fn random_position(random: &mut SmallRng) -> public::BoardState {
+        let mut tiles = [[None; 8]; 8];
+        let mut piece_counts = [0; 2];
+
+        for (row, row_tiles) in tiles.iter_mut().enumerate() {
+            for (col, tile) in row_tiles.iter_mut().enumerate() {
+                if (row + col) % 2 == 0 {
+                    continue;
+                }
+
+                let (player, is_king) = match random.gen_range(0..12) {
+                    0..=2 => (Player::Human, false),
+                    3..=5 => (Player::Cpu, false),
+                    6 => (Player::Human, true),
+                    7 => (Player::Cpu, true),
+                    _ => continue,
+                };
+                if piece_counts[player as usize] == 12 {
+                    continue;
+                }
+
+                // men on their promotion row would already have been crowned
+                let promotion_row = if player == Player::Human { 0 } else { 7 };
+                *tile = Some(public::Tile { player, is_king: is_king || row == promotion_row });
+                piece_counts[player as usize] += 1;
+            }
+        }
+
+        let turn = if random.gen_bool(0.5) { Player::Human } else { Player::Cpu };
+        public::BoardState { tiles, turn, capturing: None }
+    }
+
+    #[test]
+    fn test_random_positions() {
+        let mut random = SmallRng::seed_from_u64(13);
+        for _ in 0..2_000 {
+            let position = random_position(&mut random);
+            assert_eq!(position.validate(), Ok(()));
+            check_position(&position);
+        }
+    }
+
+    #[test]
+    fn test_random_games() {
+        let mut random = SmallRng::seed_from_u64(13);
+        for game in 0..300 {
+            // half of the games start from random positions, so kings are involved early on
+            let mut state = match game % 2 {
+                0 => slow::BoardState::default(),
+                _ => slow::BoardState::from(random_position(&mut random)),
+            };
+
+            // single steps, so positions in the middle of capture chains are covered as well
+            for _ in 0..200 {
+                check_position(&state.into());
+                let moves = sorted_moves(state);
+                let Some(mv) = moves.choose(&mut random) else { break };
+                state = state.make_move(*mv);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        // everything but two cpu pieces is irrelevant
+        let position: public::BoardState = slow::BoardState::default().into();
+        let cpu_pieces = |position: &public::BoardState| {
+            position.tiles.iter().flatten().flatten().filter(|tile| tile.player == Player::Cpu).count()
+        };
+        let minimal = shrink(&position, |position| cpu_pieces(position) >= 2);
+        assert_eq!(cpu_pieces(&minimal), 2);
+        assert_eq!(minimal.tiles.iter().flatten().flatten().count(), 2);
+    }
+}
@@ -0,0 +1,199 @@
+// positions in the PDN FEN format, e.g. "B:W21,22,K30:B1,2,3"
+// the first letter is the side to move, followed by the pieces of each color (kings prefixed with K)
+// black is the human (who moves first) and white is the cpu, see `notation` for the square numbering
+use std::fmt;
+use std::str::FromStr;
+use super::{Checkers, Player};
+use super::notation::square_to_coords;
+use super::super::public;
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FenError {
+    // the string does not have the "<turn>:<pieces>:<pieces>" structure
+    Malformed(String),
+    InvalidColor(String),
+    InvalidSquare(String),
+    // the same square is listed more than once
+    DuplicateSquare(u8),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::Malformed(fen) => write!(f, "malformed FEN '{}', expected something like 'B:W21,22,K30:B1,2,3'", fen),
+            FenError::InvalidColor(color) => write!(f, "invalid color '{}', expected 'B' or 'W'", color),
+            FenError::InvalidSquare(square) => write!(f, "invalid square '{}'", square),
+            FenError::DuplicateSquare(square) => write!(f, "square {} is occupied twice", square),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn parse_color(color: &str) -> Result<Player, FenError> {
+    match color {
+        "B" | "b" => Ok(Player::Human),
+        "W" | "w" => Ok(Player::Cpu),
+        _ => Err(FenError::InvalidColor(color.to_string())),
+    }
+}
+
+const fn color_for_player(player: Player) -> char {
+    match player {
+        Player::Human => 'B',
+        Player::Cpu => 'W',
+    }
+}
+
+fn parse_square(square: &str) -> Result<u8, FenError> {
+    square.trim().parse::<u8>().ok()
+        .filter(|square| square_to_coords(*square).is_some())
+        .ok_or_else(|| FenError::InvalidSquare(square.to_string()))
+}
+
+// a comma separated list of squares (or ranges of squares like "1-12"), kings are prefixed with K
+fn parse_pieces(pieces: &str, player: Player, tiles: &mut [[Option<public::Tile>; 8]; 8]) -> Result<(), FenError> {
+    for piece in pieces.split(',').map(str::trim).filter(|piece| !piece.is_empty()) {
+        let (is_king, squares) = match piece.strip_prefix(['K', 'k']) {
+            Some(squares) => (true, squares),
+            None => (false, piece),
+        };
+
+        let (first, last) = match squares.split_once('-') {
+            Some((first, last)) => (parse_square(first)?, parse_square(last)?),
+            None => (parse_square(squares)?, parse_square(squares)?),
+        };
+        if first > last {
+            return Err(FenError::InvalidSquare(piece.to_string()));
+        }
+
+        for square in first..=last {
+            let (row, col) = square_to_coords(square).unwrap();
+            let tile = &mut tiles[row as usize][col as usize];
+            if tile.is_some() {
+                return Err(FenError::DuplicateSquare(square));
+            }
+            *tile = Some(public::Tile { player, is_king });
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_fen(fen: &str) -> Result<public::BoardState, FenError> {
+    // some sources terminate the FEN with a period
+    let fen = fen.trim().trim_end_matches('.');
+    let sections = fen.split(':').map(str::trim).collect::<Vec<_>>();
+    let [turn, pieces @ ..] = sections.as_slice() else {
+        return Err(FenError::Malformed(fen.to_string()));
+    };
+    if pieces.len() != 2 {
+        return Err(FenError::Malformed(fen.to_string()));
+    }
+
+    let mut tiles = [[None; 8]; 8];
+    let mut seen_colors = vec![];
+    for section in pieces {
+        let Some(color) = section.get(..1) else {
+            return Err(FenError::Malformed(fen.to_string()));
+        };
+        let player = parse_color(color)?;
+        if seen_colors.contains(&player) {
+            return Err(FenError::Malformed(fen.to_string()));
+        }
+        seen_colors.push(player);
+        parse_pieces(&section[1..], player, &mut tiles)?;
+    }
+
+    Ok(public::BoardState {
+        tiles,
+        turn: parse_color(turn)?,
+        capturing: None,
+    })
+}
+
+// a position in the middle of a capture chain can not be represented, so `capturing` is dropped
+pub fn to_fen(state: &public::BoardState) -> String {
+    let pieces = |player: Player| {
+        let mut squares = vec![];
+        for square in 1..=32 {
+            let (row, col) = square_to_coords(square).unwrap();
+            match state.tiles[row as usize][col as usize] {
+                Some(tile) if tile.player == player && tile.is_king => squares.push(format!("K{}", square)),
+                Some(tile) if tile.player == player => squares.push(square.to_string()),
+                _ => (),
+            }
+        }
+        format!("{}{}", color_for_player(player), squares.join(","))
+    };
+
+    format!("{}:{}:{}", color_for_player(state.turn), pieces(Player::Cpu), pieces(Player::Human))
+}
+
+impl FromStr for public::BoardState {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_fen(s)
+    }
+}
+
+impl public::BoardState {
+    pub fn to_fen(&self) -> String {
+        to_fen(self)
+    }
+}
+
+// works for any board backend, by way of the public representation
+pub fn from_fen<C: Checkers>(fen: &str) -> Result<C, FenError> {
+    Ok(parse_fen(fen)?.into())
+}
+
+pub fn board_to_fen<C: Checkers>(state: C) -> String {
+    to_fen(&state.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, slow};
+
+    #[test]
+    fn test_default_position() {
+        let state: public::BoardState = slow::BoardState::default().into();
+        let fen = "B:W21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12";
+        assert_eq!(state.to_fen(), fen);
+        assert_eq!(fen.parse::<public::BoardState>(), Ok(state.clone()));
+        // ranges and a trailing period are accepted as well
+        assert_eq!("B:W21-32:B1-12.".parse::<public::BoardState>(), Ok(state));
+    }
+
+    #[test]
+    fn test_backends() {
+        let fen = "W:W21,22,K30:B1,2,K3";
+        let slow_state: slow::BoardState = from_fen(fen).unwrap();
+        let fast_state: fast::BoardState = from_fen(fen).unwrap();
+        assert_eq!(board_to_fen(slow_state), fen);
+        assert_eq!(board_to_fen(fast_state), fen);
+        assert_eq!(fast_state.get_turn(), Player::Cpu);
+
+        let public_state: public::BoardState = fast_state.into();
+        assert_eq!(public_state.tiles[0][5], Some(public::Tile { player: Player::Cpu, is_king: true }));
+        assert_eq!(public_state.tiles[7][2], Some(public::Tile { player: Player::Human, is_king: true }));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_fen("B:W21"), Err(FenError::Malformed("B:W21".to_string())));
+        assert_eq!(parse_fen("B:W21:W22"), Err(FenError::Malformed("B:W21:W22".to_string())));
+        assert_eq!(parse_fen("X:W21:B1"), Err(FenError::InvalidColor("X".to_string())));
+        assert_eq!(parse_fen("B:R21:B1"), Err(FenError::InvalidColor("R".to_string())));
+        assert_eq!(parse_fen("B:W33:B1"), Err(FenError::InvalidSquare("33".to_string())));
+        assert_eq!(parse_fen("B:Wx:B1"), Err(FenError::InvalidSquare("x".to_string())));
+        assert_eq!(parse_fen("B:W5-2:B1"), Err(FenError::InvalidSquare("5-2".to_string())));
+        assert_eq!(parse_fen("B:W1:B1"), Err(FenError::DuplicateSquare(1)));
+        // an empty side is fine (that player has simply lost)
+        assert!(parse_fen("W:W:B1").is_ok());
+    }
+}
@@ -0,0 +1,185 @@
+// the board states themselves are plain positions (so they can be hashed and shared between search nodes),
+// which means anything that depends on how a position was reached has to be tracked separately
+use super::{Checkers, DrawReason, GameOutcome, IllegalMove, Move};
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct DrawRules {
+    // the game is drawn after this many moves by *each* player without a capture or a man being moved
+    pub no_progress_moves: usize,
+    // the game is drawn once the same position (including the side to move) has occurred this many times
+    pub repetitions: usize,
+}
+
+impl Default for DrawRules {
+    fn default() -> Self {
+        Self {
+            no_progress_moves: 40,
+            repetitions: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameHistory<C: Checkers> {
+    rules: DrawRules,
+    // all positions since the last progress move (the last one being the current position)
+    // earlier positions can never be repeated, so there is no point in keeping them around
+    positions: Vec<C>,
+}
+
+impl<C: Checkers> GameHistory<C> {
+    pub fn new(state: C) -> Self {
+        Self::with_rules(state, DrawRules::default())
+    }
+
+    pub fn with_rules(state: C, rules: DrawRules) -> Self {
+        Self {
+            rules,
+            positions: vec![state],
+        }
+    }
+
+    pub fn current(&self) -> C {
+        *self.positions.last().unwrap()
+    }
+
+    pub fn plies_without_progress(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    pub fn make_move(&mut self, mv: Move) -> C {
+        let state = self.current();
+        self.push(state, mv, state.make_move(mv))
+    }
+
+    pub fn try_make_move(&mut self, mv: Move) -> Result<C, IllegalMove> {
+        let outcome = self.get_outcome();
+        if outcome != GameOutcome::Ongoing {
+            return Err(IllegalMove::GameOver(outcome));
+        }
+
+        let state = self.current();
+        let next_state = state.try_make_move(mv)?;
+        // like in `Checkers::try_make_move`, `is_skip_move` is implied by the squares
+        let mv = Move { is_skip_move: mv.from.0.abs_diff(mv.to.0) == 2, ..mv };
+        Ok(self.push(state, mv, next_state))
+    }
+
+    fn push(&mut self, state: C, mv: Move, next_state: C) -> C {
+        if state.is_progress_move(mv) {
+            self.positions.clear();
+        }
+        self.positions.push(next_state);
+
+        next_state
+    }
+
+    pub fn get_outcome(&self) -> GameOutcome {
+        let state = self.current();
+        let outcome = state.get_outcome();
+        if outcome != GameOutcome::Ongoing {
+            return outcome;
+        }
+
+        // comparing the keys first skips most of the full comparisons
+        let hash = state.zobrist_hash();
+        let occurrences = self.positions.iter().filter(|position| position.zobrist_hash() == hash && **position == state).count();
+        if occurrences >= self.rules.repetitions {
+            return GameOutcome::Draw(DrawReason::Repetition);
+        }
+
+        if self.plies_without_progress() >= 2 * self.rules.no_progress_moves {
+            return GameOutcome::Draw(DrawReason::NoProgress);
+        }
+
+        GameOutcome::Ongoing
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, Player};
+    use crate::public;
+
+    // a human king on 34 and a cpu king on 0, nothing else on the board
+    fn kings_only() -> fast::BoardState {
+        fast::BoardState::new(1 << 34, 1 << 0, (1 << 34) | (1 << 0), Player::Human)
+    }
+
+    #[test]
+    fn test_repetition() {
+        let mut history = GameHistory::new(kings_only());
+        let shuffle = [
+            Move { from: (7, 6), to: (6, 7), is_skip_move: false },
+            Move { from: (0, 1), to: (1, 0), is_skip_move: false },
+            Move { from: (6, 7), to: (7, 6), is_skip_move: false },
+            Move { from: (1, 0), to: (0, 1), is_skip_move: false },
+        ];
+
+        for mv in shuffle {
+            assert_eq!(history.get_outcome(), GameOutcome::Ongoing);
+            history.make_move(mv);
+        }
+        // the starting position has now occurred twice
+        for mv in shuffle {
+            assert_eq!(history.get_outcome(), GameOutcome::Ongoing);
+            history.make_move(mv);
+        }
+        assert_eq!(history.get_outcome(), GameOutcome::Draw(DrawReason::Repetition));
+    }
+
+    #[test]
+    fn test_no_progress() {
+        let rules = DrawRules { no_progress_moves: 2, repetitions: usize::MAX };
+        let mut history = GameHistory::with_rules(kings_only(), rules);
+        let moves = [
+            Move { from: (7, 6), to: (6, 7), is_skip_move: false },
+            Move { from: (0, 1), to: (1, 0), is_skip_move: false },
+            Move { from: (6, 7), to: (5, 6), is_skip_move: false },
+        ];
+
+        for mv in moves {
+            history.make_move(mv);
+            assert_eq!(history.get_outcome(), GameOutcome::Ongoing);
+        }
+        history.make_move(Move { from: (1, 0), to: (2, 1), is_skip_move: false });
+        assert_eq!(history.get_outcome(), GameOutcome::Draw(DrawReason::NoProgress));
+    }
+
+    #[test]
+    fn test_try_make_move() {
+        let rules = DrawRules { no_progress_moves: 1, repetitions: usize::MAX };
+        let mut history = GameHistory::with_rules(kings_only(), rules);
+        assert_eq!(
+            history.try_make_move(Move { from: (0, 1), to: (1, 0), is_skip_move: false }),
+            Err(IllegalMove::WrongSide)
+        );
+        history.try_make_move(Move { from: (7, 6), to: (6, 7), is_skip_move: false }).unwrap();
+        history.try_make_move(Move { from: (0, 1), to: (1, 0), is_skip_move: false }).unwrap();
+        assert_eq!(
+            history.try_make_move(Move { from: (6, 7), to: (7, 6), is_skip_move: false }),
+            Err(IllegalMove::GameOver(GameOutcome::Draw(DrawReason::NoProgress)))
+        );
+    }
+
+    #[test]
+    fn test_progress_resets_history() {
+        let mut history = GameHistory::new(<fast::BoardState as Checkers>::default());
+        history.make_move(Move { from: (5, 0), to: (4, 1), is_skip_move: false });
+        assert_eq!(history.plies_without_progress(), 0);
+
+        // a capture by a king is progress, even when the move from the frontend leaves `is_skip_move` unset
+        let mut tiles = [[None; 8]; 8];
+        tiles[7][6] = Some(public::Tile { player: Player::Human, is_king: true });
+        tiles[6][5] = Some(public::Tile { player: Player::Cpu, is_king: true });
+        tiles[0][1] = Some(public::Tile { player: Player::Cpu, is_king: true });
+        let mut history = GameHistory::new(fast::BoardState::from(public::BoardState { tiles, turn: Player::Cpu, capturing: None }));
+        history.try_make_move(Move { from: (0, 1), to: (1, 0), is_skip_move: false }).unwrap();
+        assert_eq!(history.plies_without_progress(), 1);
+        history.try_make_move(Move { from: (7, 6), to: (5, 4), is_skip_move: false }).unwrap();
+        assert_eq!(history.plies_without_progress(), 0);
+    }
+}
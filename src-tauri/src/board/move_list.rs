@@ -0,0 +1,122 @@
+// a fixed-capacity list of moves that lives on the stack, so generating moves does not allocate
+// it dereferences to a slice, which is how it is meant to be read (iterating, `choose`, indexing, ...)
+use std::ops::{Deref, DerefMut};
+use super::Move;
+
+
+// no position has more moves than this: 12 kings with 4 directions each is the worst case
+pub const MAX_MOVES: usize = 64;
+
+const EMPTY: Move = Move { from: (0, 0), to: (0, 0), is_skip_move: false };
+
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub const fn new() -> Self {
+        Self {
+            moves: [EMPTY; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Move) -> bool) {
+        let mut len = 0;
+        for i in 0..self.len {
+            if keep(&self.moves[i]) {
+                self.moves[len] = self.moves[i];
+                len += 1;
+            }
+        }
+        self.len = len;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl std::fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for MoveList {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for MoveList {}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Move> for MoveList {
+    fn from_iter<I: IntoIterator<Item = Move>>(iter: I) -> Self {
+        let mut moves = Self::new();
+        for mv in iter {
+            moves.push(mv);
+        }
+        moves
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_list() {
+        let mut moves = MoveList::new();
+        assert!(moves.is_empty());
+
+        moves.push(Move { from: (5, 0), to: (4, 1), is_skip_move: false });
+        moves.push(Move { from: (5, 2), to: (3, 4), is_skip_move: true });
+        moves.push(Move { from: (5, 2), to: (4, 3), is_skip_move: false });
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[1].to, (3, 4));
+
+        moves.retain(|mv| !mv.is_skip_move);
+        assert_eq!(moves.iter().map(|mv| mv.to).collect::<Vec<_>>(), vec![(4, 1), (4, 3)]);
+
+        moves.clear();
+        assert!(moves.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+// standard checkers notation, where the 32 dark squares are numbered 1-32
+// black (the human, who moves first) starts on 1-12 and white (the cpu) on 21-32:
+//    -------CPU--------
+//  0|--32--31--30--29|
+//  1|28--27--26--25--|
+//  2|--24--23--22--21|
+//  3|20--19--18--17--|
+//  4|--16--15--14--13|
+//  5|12--11--10--09--|
+//  6|--08--07--06--05|
+//  7|04--03--02--01--|
+//    -------HUMAN------
+use std::fmt;
+use std::str::FromStr;
+use super::{fast, CompoundMove, Move};
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NotationError {
+    // a square that is not a number from 1 to 32
+    InvalidSquare(String),
+    // a move that does not consist of at least two squares, or whose squares are not a step or jump apart
+    InvalidMove(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::InvalidSquare(square) => write!(f, "invalid square '{}'", square),
+            NotationError::InvalidMove(mv) => write!(f, "invalid move '{}'", mv),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+pub fn coords_to_square(coords: (u8, u8)) -> Option<u8> {
+    let (row, col) = coords;
+    if row >= 8 || col >= 8 || (row + col) % 2 == 0 {
+        return None;
+    }
+    Some(32 - (row * 4 + col / 2))
+}
+
+pub fn square_to_coords(square: u8) -> Option<(u8, u8)> {
+    if !(1..=32).contains(&square) {
+        return None;
+    }
+    let index = 32 - square;
+    let row = index / 4;
+    Some((row, 2 * (index % 4) + (row + 1) % 2))
+}
+
+fn parse_square(square: &str) -> Result<(u8, u8), NotationError> {
+    square.trim().parse::<u8>().ok()
+        .and_then(square_to_coords)
+        .ok_or_else(|| NotationError::InvalidSquare(square.to_string()))
+}
+
+fn format_square(coords: (u8, u8)) -> String {
+    match coords_to_square(coords) {
+        Some(square) => square.to_string(),
+        None => format!("{:?}", coords),
+    }
+}
+
+// whether a move is a capture follows from the squares, so both "-" and "x" are accepted as separators
+pub fn parse_squares(s: &str) -> Result<Vec<(u8, u8)>, NotationError> {
+    let squares = s.split(['-', 'x', 'X']).map(parse_square).collect::<Result<Vec<_>, _>>()?;
+    if squares.len() < 2 {
+        return Err(NotationError::InvalidMove(s.to_string()));
+    }
+    Ok(squares)
+}
+
+fn step_distance(from: (u8, u8), to: (u8, u8)) -> (u8, u8) {
+    (from.0.abs_diff(to.0), from.1.abs_diff(to.1))
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.is_skip_move { "x" } else { "-" };
+        write!(f, "{}{}{}", format_square(self.from), separator, format_square(self.to))
+    }
+}
+
+impl FromStr for Move {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_squares(s)?.as_slice() {
+            [from, to] => match step_distance(*from, *to) {
+                (1, 1) => Ok(Move { from: *from, to: *to, is_skip_move: false }),
+                (2, 2) => Ok(Move { from: *from, to: *to, is_skip_move: true }),
+                _ => Err(NotationError::InvalidMove(s.to_string())),
+            },
+            // multiple captures are only representable by a `CompoundMove`
+            _ => Err(NotationError::InvalidMove(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for CompoundMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.is_capture() { "x" } else { "-" };
+        write!(f, "{}", format_square(self.from))?;
+        for square in &self.path {
+            write!(f, "{}{}", separator, format_square(*square))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CompoundMove {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let squares = parse_squares(s)?;
+        let mut mv = CompoundMove {
+            from: squares[0],
+            path: vec![],
+            captured: vec![],
+        };
+
+        for (from, to) in squares.iter().zip(squares.iter().skip(1)) {
+            match (step_distance(*from, *to), squares.len()) {
+                ((1, 1), 2) => mv.path.push(*to),
+                ((2, 2), _) => {
+                    mv.path.push(*to);
+                    mv.captured.push(((from.0 + to.0) / 2, (from.1 + to.1) / 2));
+                },
+                _ => return Err(NotationError::InvalidMove(s.to_string())),
+            }
+        }
+
+        Ok(mv)
+    }
+}
+
+impl fmt::Display for fast::Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Move::from(*self).fmt(f)
+    }
+}
+
+impl FromStr for fast::Move {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<Move>()?.try_into().map_err(|_| NotationError::InvalidMove(s.to_string()))
+    }
+}
+
+// turns a sequence of single steps into the usual move list, merging the steps of a capture chain
+// (a chain continues as long as the next capture starts where the last one ended, which the opponent never can)
+pub fn format_moves(moves: &[Move]) -> Vec<String> {
+    merge_steps(moves).iter().map(|mv| mv.to_string()).collect()
+}
+
+pub fn merge_steps(moves: &[Move]) -> Vec<CompoundMove> {
+    let mut compound_moves: Vec<CompoundMove> = vec![];
+    for mv in moves {
+        match compound_moves.last_mut() {
+            Some(last) if mv.is_skip_move && last.is_capture() && last.to() == mv.from => {
+                last.path.push(mv.to);
+                last.captured.push(((mv.from.0 + mv.to.0) / 2, (mv.from.1 + mv.to.1) / 2));
+            },
+            _ => compound_moves.push((*mv).into()),
+        }
+    }
+    compound_moves
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{slow, Checkers};
+
+    #[test]
+    fn test_squares() {
+        assert_eq!(square_to_coords(1), Some((7, 6)));
+        assert_eq!(square_to_coords(4), Some((7, 0)));
+        assert_eq!(square_to_coords(5), Some((6, 7)));
+        assert_eq!(square_to_coords(32), Some((0, 1)));
+        assert_eq!(square_to_coords(0), None);
+        assert_eq!(square_to_coords(33), None);
+        assert_eq!(coords_to_square((0, 0)), None);
+
+        for square in 1..=32 {
+            assert_eq!(coords_to_square(square_to_coords(square).unwrap()), Some(square));
+        }
+    }
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!("11-15".parse(), Ok(Move { from: (5, 2), to: (4, 3), is_skip_move: false }));
+        assert_eq!("22x15".parse(), Ok(Move { from: (2, 5), to: (4, 3), is_skip_move: true }));
+        // the capture is implied by the squares
+        assert_eq!("22-15".parse::<Move>(), "22x15".parse::<Move>());
+
+        assert!("11-19".parse::<Move>().is_err());
+        assert!("11".parse::<Move>().is_err());
+        assert!("22x15x6".parse::<Move>().is_err());
+        assert_eq!("0-4".parse::<Move>(), Err(NotationError::InvalidSquare("0".to_string())));
+        assert_eq!("a-4".parse::<Move>(), Err(NotationError::InvalidSquare("a".to_string())));
+    }
+
+    #[test]
+    fn test_compound_move() {
+        let mv: CompoundMove = "22x15x6".parse().unwrap();
+        assert_eq!(mv, CompoundMove {
+            from: (2, 5),
+            path: vec![(4, 3), (6, 5)],
+            captured: vec![(3, 4), (5, 4)],
+        });
+        assert_eq!(mv.to_string(), "22x15x6");
+        assert_eq!("9-14".parse::<CompoundMove>().unwrap().to_string(), "9-14");
+        // simple moves can not be chained
+        assert!("9-14-18".parse::<CompoundMove>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let mv = Move { from: (5, 2), to: (4, 3), is_skip_move: false };
+        assert_eq!(mv.to_string(), "11-15");
+        assert_eq!(fast::Move::try_from(mv).unwrap().to_string(), "11-15");
+        assert_eq!("11-15".parse::<fast::Move>(), Ok(fast::Move::try_from(mv).unwrap()));
+    }
+
+    #[test]
+    fn test_opening_moves() {
+        let mut moves = slow::BoardState::default().get_legal_moves().iter().map(|mv| mv.to_string()).collect::<Vec<_>>();
+        moves.sort();
+        assert_eq!(moves, vec!["10-14", "10-15", "11-15", "11-16", "12-16", "9-13", "9-14"]);
+
+        let state = <fast::BoardState as Checkers>::default();
+        assert!(state.try_make_move("11-15".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_format_moves() {
+        let moves = [
+            "11-15".parse().unwrap(),
+            "22-18".parse().unwrap(),
+            "15x22".parse().unwrap(),
+            "25x18".parse().unwrap(),
+            "18x11".parse().unwrap(),
+        ];
+        assert_eq!(format_moves(&moves), vec!["11-15", "22-18", "15x22", "25x18x11"]);
+    }
+}
@@ -0,0 +1,94 @@
+// perft counts the leaf nodes of the game tree up to a fixed depth, which can be compared to published counts to
+// check the move generator. a ply is a complete turn here (a multi-jump counts once), like in the published counts
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use super::{Checkers, CompoundMove};
+
+
+// the published counts for american checkers from the starting position, indexed by depth
+pub const STARTING_POSITION_COUNTS: [u64; 11] = [1, 7, 49, 302, 1469, 7361, 36768, 179740, 845931, 3963680, 18391564];
+
+pub fn perft<C: Checkers>(state: C, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = state.get_legal_compound_moves();
+    // the leaves do not have to be played out, only counted
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.iter().map(|mv| perft(state.make_compound_move(mv), depth - 1)).sum()
+}
+
+// the counts below each move of the root, which narrows down where two generators disagree
+pub fn divide<C: Checkers>(state: C, depth: usize) -> Vec<(CompoundMove, u64)> {
+    state.get_legal_compound_moves().into_iter().map(|mv| {
+        let count = perft(state.make_compound_move(&mv), depth.saturating_sub(1));
+        (mv, count)
+    }).collect()
+}
+
+// like `divide`, but the root moves are handed out to `threads` threads
+pub fn divide_parallel<C: Checkers + Send + Sync>(state: C, depth: usize, threads: usize) -> Vec<(CompoundMove, u64)> {
+    let moves = state.get_legal_compound_moves();
+    let counts = moves.iter().map(|_| AtomicU64::new(0)).collect::<Vec<_>>();
+    let next_move = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let i = next_move.fetch_add(1, Ordering::Relaxed);
+                let Some(mv) = moves.get(i) else { break };
+                let count = perft(state.make_compound_move(mv), depth.saturating_sub(1));
+                counts[i].store(count, Ordering::Relaxed);
+            });
+        }
+    });
+
+    moves.into_iter().zip(counts).map(|(mv, count)| (mv, count.into_inner())).collect()
+}
+
+pub fn perft_parallel<C: Checkers + Send + Sync>(state: C, depth: usize, threads: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    divide_parallel(state, depth, threads).iter().map(|(_, count)| count).sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, slow};
+
+    #[test]
+    fn test_perft_fast() {
+        for (depth, count) in STARTING_POSITION_COUNTS.iter().enumerate().take(7) {
+            assert_eq!(perft(<fast::BoardState as Checkers>::default(), depth), *count, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_slow() {
+        for (depth, count) in STARTING_POSITION_COUNTS.iter().enumerate().take(6) {
+            assert_eq!(perft(slow::BoardState::default(), depth), *count, "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        let state = <fast::BoardState as Checkers>::default();
+        let divided = divide(state, 4);
+        assert_eq!(divided.len(), 7);
+        assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), STARTING_POSITION_COUNTS[4]);
+        assert_eq!(divide_parallel(state, 4, 3), divided);
+    }
+
+    #[test]
+    fn test_perft_parallel() {
+        let state = <fast::BoardState as Checkers>::default();
+        assert_eq!(perft_parallel(state, 0, 4), 1);
+        assert_eq!(perft_parallel(state, 6, 4), STARTING_POSITION_COUNTS[6]);
+        assert_eq!(perft_parallel(state, 6, 0), STARTING_POSITION_COUNTS[6]);
+    }
+}
@@ -1,6 +1,7 @@
 // this is the less efficient implementation of the checkers board
 // it does not use bitboards and is not optimized for speed
-use super::{Checkers, Player, Move};
+use super::{zobrist, Checkers, GameOutcome, Player, Move};
+use super::move_list::MoveList;
 use super::super::public;
 
 
@@ -17,10 +18,29 @@ pub struct Tile {
     pub is_king: bool
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy)]
 pub struct BoardState {
     tiles: [[Option<Tile>; 8]; 8],
-    turn: Player
+    turn: Player,
+    // the piece that has to continue capturing, if the turn is in the middle of a capture chain
+    capturing: Option<(u8, u8)>,
+    // the zobrist key of the fields above
+    hash: u64
+}
+
+// the key is derived from the position, so it is left out of comparisons and is all that needs hashing
+impl PartialEq for BoardState {
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles && self.turn == other.turn && self.capturing == other.capturing
+    }
+}
+
+impl Eq for BoardState {}
+
+impl std::hash::Hash for BoardState {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
 }
 
 impl From<public::Tile> for Tile {
@@ -46,7 +66,9 @@ impl From<public::BoardState> for BoardState {
     fn from(value: public::BoardState) -> Self {
         BoardState {
             tiles: value.tiles.map(|row| row.map(|tile| tile.map(|tile| tile.into()))),
-            turn: value.turn
+            turn: value.turn,
+            capturing: value.capturing,
+            hash: zobrist::hash_position(&value)
         }
     }
 }
@@ -55,7 +77,8 @@ impl From<BoardState> for public::BoardState {
     fn from(value: BoardState) -> Self {
         public::BoardState {
             tiles: value.tiles.map(|row| row.map(|tile| tile.map(|tile| tile.into()))),
-            turn: value.turn
+            turn: value.turn,
+            capturing: value.capturing
         }
     }
 }
@@ -104,17 +127,29 @@ impl Checkers for BoardState {
             }
         }
 
-        Self {
+        let mut state = Self {
             tiles,
-            turn: Player::Human
-        }
+            turn: Player::Human,
+            capturing: None,
+            hash: 0
+        };
+        state.hash = zobrist::hash_position(&state.into());
+        state
     }
 
     fn get_turn(&self) -> Player {
         self.turn
     }
 
-    fn is_game_over(&self) -> bool {
+    fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn get_tile(&self, pos: (u8, u8)) -> Option<public::Tile> {
+        self.tiles.get(pos.0 as usize)?.get(pos.1 as usize)?.map(|tile| tile.into())
+    }
+
+    fn get_outcome(&self) -> GameOutcome {
         let has_human_tiles = self.tiles.iter().flatten().any(|tile| {
             if let Some(tile) = tile {
                 tile.player == Player::Human
@@ -131,36 +166,47 @@ impl Checkers for BoardState {
             }
         });
 
-        !has_human_tiles || !has_cpu_tiles
+        match (has_human_tiles, has_cpu_tiles) {
+            // a player that can not move loses
+            (true, true) if self.get_legal_move_list().is_empty() => GameOutcome::Win(!self.turn),
+            (true, true) => GameOutcome::Ongoing,
+            (true, false) => GameOutcome::Win(Player::Human),
+            (false, true) => GameOutcome::Win(Player::Cpu),
+            (false, false) => GameOutcome::Win(!self.turn)
+        }
     }
 
-    fn get_winner(&self) -> Option<Player> {
-        let first_valid_tile = self.tiles.iter().flatten().find(|tile| tile.is_some()).unwrap();
-        Some(first_valid_tile.unwrap().player)
+    fn is_progress_move(&self, mv: Move) -> bool {
+        if !super::is_on_board(mv.from) {
+            return false;
+        }
+        let tile = self.tiles[mv.from.0 as usize][mv.from.1 as usize];
+        mv.is_skip_move || !tile.is_some_and(|tile| tile.is_king)
     }
 
-    fn get_legal_moves(&self) -> Vec<Move> {
-        let own_tiles = self.tiles.iter().flatten().enumerate().filter(|(i,tile)| match tile {
-            Some(tile) if tile.player == self.turn => true,
+    fn get_legal_move_list(&self) -> MoveList {
+        // in the middle of a capture chain, only the capturing piece may move
+        let own_tiles = self.tiles.iter().flatten().enumerate().filter(|(i, tile)| match tile {
+            Some(tile) if tile.player == self.turn => self.capturing.map_or(true, |pos| pos == ((i / 8) as u8, (i % 8) as u8)),
             _ => false
         });
         
-        let mut moves = own_tiles.flat_map(|(i, tile)| {
+        let mut moves = MoveList::new();
+        for (i, tile) in own_tiles {
             let tile = tile.unwrap();
             let (row, col) = (i / 8, i % 8);
-            let mut moves = vec![];
 
             // cpu moves down (i.e. increasing row)
             // human moves up (i.e. decreasing row)
             // kings can move in both directions
             let row_dir = match (tile.player, tile.is_king) {
-                (_, true) => vec![-1, 1],
-                (Player::Cpu, false) => vec![1],
-                (Player::Human, false) => vec![-1]
+                (_, true) => &[-1, 1][..],
+                (Player::Cpu, false) => &[1][..],
+                (Player::Human, false) => &[-1][..]
             };
 
-            for row_offset in row_dir {
-                for col_offset in vec![-1, 1] {
+            for &row_offset in row_dir {
+                for col_offset in [-1, 1] {
                     let new_row = row as i8 + row_offset;
                     let new_col = col as i8 + col_offset;
 
@@ -197,14 +243,10 @@ impl Checkers for BoardState {
                     }
                 }
             }
-
-            
-
-            moves
-        }).collect::<Vec<Move>>();
+        }
 
         // if moves contain any skip moves, filter out non-skip moves
-        if moves.iter().any(|m| m.is_skip_move) {
+        if self.capturing.is_some() || moves.iter().any(|m| m.is_skip_move) {
             moves.retain(|m| m.is_skip_move);
         }
         moves
@@ -218,33 +260,58 @@ impl Checkers for BoardState {
         new_tiles[to_row][to_col] = new_tiles[from_row][from_col];
         new_tiles[from_row][from_col] = None;
 
+        let mut hash = self.hash;
+        if let Some(tile) = self.tiles[from_row][from_col] {
+            hash ^= zobrist::piece_key(mv.from, tile.player, tile.is_king);
+        }
+        if let Some(coords) = self.capturing {
+            hash ^= zobrist::capturing_key(coords);
+        }
+
         // check for king promotion
+        let mut is_promoted = false;
         if let Some(tile) = new_tiles[to_row][to_col] {
-            if (tile.player == Player::Cpu && to_row == 7) || (tile.player == Player::Human && to_row == 0) {
+            if !tile.is_king && ((tile.player == Player::Cpu && to_row == 7) || (tile.player == Player::Human && to_row == 0)) {
                 new_tiles[to_row][to_col] = Some(Tile {
                     player: tile.player,
                     is_king: true
                 });
+                is_promoted = true;
             }
         }
 
+        if let Some(tile) = new_tiles[to_row][to_col] {
+            hash ^= zobrist::piece_key(mv.to, tile.player, tile.is_king);
+        }
+
         if mv.is_skip_move {
             let skip_row = (from_row + to_row) / 2;
             let skip_col = (from_col + to_col) / 2;
+            if let Some(tile) = new_tiles[skip_row][skip_col] {
+                hash ^= zobrist::piece_key((skip_row as u8, skip_col as u8), tile.player, tile.is_king);
+            }
             new_tiles[skip_row][skip_col] = None;
         }
 
-        let mut next_state = Self {
-            tiles: new_tiles,
-            turn: self.turn
-        };
-
-        if !mv.is_skip_move || !next_state.get_legal_moves().iter().any(|m| m.is_skip_move) {
-            next_state.turn = !self.turn;
+        // the capturing piece keeps the turn as long as it can capture again (unless it was just crowned)
+        if mv.is_skip_move && !is_promoted {
+            let continuing_state = Self {
+                tiles: new_tiles,
+                turn: self.turn,
+                capturing: Some(mv.to),
+                hash: hash ^ zobrist::capturing_key(mv.to)
+            };
+            if !continuing_state.get_legal_move_list().is_empty() {
+                return continuing_state;
+            }
         }
-            
-        next_state
 
+        Self {
+            tiles: new_tiles,
+            turn: !self.turn,
+            capturing: None,
+            hash: hash ^ zobrist::TURN_KEY
+        }
     }
 }
 
@@ -266,11 +333,51 @@ mod tests {
         assert_eq!(moves.len(), 7);
     }
 
+    // positions are set up on the public board, so that the zobrist key matches them
+    fn empty_board(turn: Player) -> public::BoardState {
+        public::BoardState { tiles: [[None; 8]; 8], turn, capturing: None }
+    }
+
     #[test]
     fn test_is_game_over() {
-        let mut board = BoardState::default();
+        let board = BoardState::default();
         assert_eq!(board.is_game_over(), false);
-        board.tiles = [[None; 8]; 8];
+        let board = BoardState::from(empty_board(Player::Human));
         assert_eq!(board.is_game_over(), true);
     }
+
+    #[test]
+    fn test_get_outcome() {
+        let board = BoardState::default();
+        assert_eq!(board.get_outcome(), GameOutcome::Ongoing);
+        let mut position = empty_board(Player::Human);
+        position.tiles[0][1] = Some(public::Tile { player: Player::Cpu, is_king: false });
+        assert_eq!(BoardState::from(position).get_outcome(), GameOutcome::Win(Player::Cpu));
+    }
+
+    #[test]
+    fn test_blocked_player_loses() {
+        let mut position = empty_board(Player::Human);
+        // the human man in the corner can neither move nor capture
+        position.tiles[7][0] = Some(public::Tile { player: Player::Human, is_king: false });
+        position.tiles[6][1] = Some(public::Tile { player: Player::Cpu, is_king: false });
+        position.tiles[5][2] = Some(public::Tile { player: Player::Cpu, is_king: false });
+        let board = BoardState::from(position.clone());
+        assert!(board.get_legal_moves().is_empty());
+        assert_eq!(board.get_outcome(), GameOutcome::Win(Player::Cpu));
+
+        // the cpu is not blocked though
+        position.turn = Player::Cpu;
+        assert_eq!(BoardState::from(position).get_outcome(), GameOutcome::Ongoing);
+    }
+
+    #[test]
+    fn test_is_progress_move() {
+        let board = BoardState::default();
+        assert!(board.is_progress_move(Move { from: (5, 0), to: (4, 1), is_skip_move: false }));
+        let mut position = public::BoardState::from(board);
+        position.tiles[5][0] = Some(public::Tile { player: Player::Human, is_king: true });
+        assert!(!BoardState::from(position).is_progress_move(Move { from: (5, 0), to: (4, 1), is_skip_move: false }));
+        assert!(!board.is_progress_move(Move { from: (9, 0), to: (7, 2), is_skip_move: true }));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,511 @@
+// endgame tablebases: the exact value of every position with up to `max_pieces` pieces (and how many turns it takes
+// to win or lose), computed by retrograde analysis on the bitboard representation
+//
+// positions are grouped by their material (e.g. two human kings against one cpu king), and each material gets a
+// table with one byte per index. an index packs the squares of each kind of piece as a combination (so the table
+// also has entries for overlapping pieces, which are never probed) and the side to move
+// values are from the perspective of the side to move, and only the board itself counts (no repetition draws)
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use super::{fast, Checkers, CompoundMove, GameOutcome, Player};
+
+
+pub const DEFAULT_MAX_PIECES: usize = 3;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Value {
+    // the side to move wins within this many turns (counting both players' turns)
+    Win(u8),
+    // the side to move loses after this many turns, 0 meaning it can not move at all
+    Loss(u8),
+    Draw,
+}
+
+// the longest distance a byte can hold, none of the small endgames come close
+const MAX_DISTANCE: u8 = 127;
+
+impl Value {
+    // 0 is a draw (and what the unused entries hold), then wins and losses with the distance in the lower 7 bits
+    fn to_byte(self) -> u8 {
+        match self {
+            Value::Draw => 0,
+            Value::Win(distance) => distance,
+            Value::Loss(distance) => 128 + distance,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        match byte {
+            0 => Value::Draw,
+            1..=127 => Value::Win(byte),
+            _ => Value::Loss(byte - 128),
+        }
+    }
+
+    // the value for the player that made the move leading to a position with this value
+    fn previous(self) -> Self {
+        match self {
+            Value::Win(distance) => Value::Loss(distance + 1),
+            Value::Loss(distance) => Value::Win(distance + 1),
+            Value::Draw => Value::Draw,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Material {
+    pub human_men: u8,
+    pub human_kings: u8,
+    pub cpu_men: u8,
+    pub cpu_kings: u8,
+}
+
+// the 32 dark squares in the ghost-square layout of `fast`, in order
+const SQUARES: [usize; 32] = {
+    let mut squares = [0; 32];
+    let (mut position, mut i) = (0, 0);
+    while i < 32 {
+        if position % 9 != 8 {
+            squares[i] = position;
+            i += 1;
+        }
+        position += 1;
+    }
+    squares
+};
+
+// pascal's triangle, for ranking combinations of squares
+const BINOMIAL: [[usize; 13]; 33] = {
+    let mut binomial = [[0; 13]; 33];
+    let mut n = 0;
+    while n < 33 {
+        binomial[n][0] = 1;
+        let mut k = 1;
+        while k < 13 && k <= n {
+            binomial[n][k] = binomial[n - 1][k - 1] + if k < n { binomial[n - 1][k] } else { 0 };
+            k += 1;
+        }
+        n += 1;
+    }
+    binomial
+};
+
+// men on their promotion row would have been crowned
+const HUMAN_PROMOTION_SQUARES: u32 = 0b1111;
+const CPU_PROMOTION_SQUARES: u32 = 0b1111 << 28;
+
+fn to_squares(bits: u64) -> u32 {
+    SQUARES.iter().enumerate().fold(0, |squares, (i, position)| squares | (((bits >> position) & 1) as u32) << i)
+}
+
+fn to_bits(squares: u32) -> u64 {
+    SQUARES.iter().enumerate().fold(0, |bits, (i, position)| bits | (((squares >> i) & 1) as u64) << position)
+}
+
+// the combinatorial number system: the sorted squares s_1 < .. < s_k get the rank C(s_1, 1) + .. + C(s_k, k)
+fn rank(squares: u32) -> usize {
+    (0..32).filter(|square| squares & (1 << square) != 0).enumerate().map(|(i, square)| BINOMIAL[square][i + 1]).sum()
+}
+
+fn unrank(mut rank: usize, count: usize) -> u32 {
+    let mut squares = 0;
+    for k in (1..=count).rev() {
+        let square = (k - 1..32).rev().find(|square| BINOMIAL[*square][k] <= rank).unwrap();
+        rank -= BINOMIAL[square][k];
+        squares |= 1 << square;
+    }
+    squares
+}
+
+impl Material {
+    pub fn of(state: &fast::BoardState) -> Self {
+        Self {
+            human_men: (state.tiles_human() & !state.kings()).count_ones() as u8,
+            human_kings: (state.tiles_human() & state.kings()).count_ones() as u8,
+            cpu_men: (state.tiles_cpu() & !state.kings()).count_ones() as u8,
+            cpu_kings: (state.tiles_cpu() & state.kings()).count_ones() as u8,
+        }
+    }
+
+    fn counts(&self) -> [u8; 4] {
+        [self.human_men, self.human_kings, self.cpu_men, self.cpu_kings]
+    }
+
+    pub fn pieces(&self) -> usize {
+        self.counts().iter().map(|count| *count as usize).sum()
+    }
+
+    fn size(&self) -> usize {
+        2 * self.counts().iter().map(|count| BINOMIAL[32][*count as usize]).product::<usize>()
+    }
+
+    // every material with both players on the board, in an order where captures and promotions (the moves that
+    // change the material) always lead to materials that come earlier
+    pub fn all(max_pieces: usize) -> Vec<Self> {
+        let mut materials = vec![];
+        for pieces in 2..=max_pieces {
+            for human_men in 0..=pieces {
+                for human_kings in 0..=pieces - human_men {
+                    for cpu_men in 0..=pieces - human_men - human_kings {
+                        let cpu_kings = pieces - human_men - human_kings - cpu_men;
+                        let material = Material {
+                            human_men: human_men as u8,
+                            human_kings: human_kings as u8,
+                            cpu_men: cpu_men as u8,
+                            cpu_kings: cpu_kings as u8,
+                        };
+                        if human_men + human_kings > 0 && cpu_men + cpu_kings > 0 {
+                            materials.push(material);
+                        }
+                    }
+                }
+            }
+        }
+        materials.sort_by_key(|material| (material.pieces(), material.human_men + material.cpu_men));
+        materials
+    }
+
+    fn index(&self, state: &fast::BoardState) -> usize {
+        let classes = [
+            state.tiles_human() & !state.kings(),
+            state.tiles_human() & state.kings(),
+            state.tiles_cpu() & !state.kings(),
+            state.tiles_cpu() & state.kings(),
+        ];
+        let index = classes.iter().zip(self.counts()).fold(0, |index, (bits, count)| {
+            index * BINOMIAL[32][count as usize] + rank(to_squares(*bits))
+        });
+        2 * index + state.get_turn() as usize
+    }
+
+    // the position at this index, if it is one that can actually occur
+    fn position(&self, index: usize) -> Option<fast::BoardState> {
+        let turn = if index % 2 == 0 { Player::Human } else { Player::Cpu };
+        let mut index = index / 2;
+
+        let mut classes = [0; 4];
+        for (class, count) in classes.iter_mut().zip(self.counts()).rev() {
+            let combinations = BINOMIAL[32][count as usize];
+            *class = unrank(index % combinations, count as usize);
+            index /= combinations;
+        }
+
+        let [human_men, human_kings, cpu_men, cpu_kings] = classes;
+        let overlapping = (human_men | human_kings | cpu_men | cpu_kings).count_ones() as usize != self.pieces();
+        if overlapping || human_men & HUMAN_PROMOTION_SQUARES != 0 || cpu_men & CPU_PROMOTION_SQUARES != 0 {
+            return None;
+        }
+
+        Some(fast::BoardState::new(
+            to_bits(human_men | human_kings),
+            to_bits(cpu_men | cpu_kings),
+            to_bits(human_kings | cpu_kings),
+            turn,
+        ))
+    }
+}
+
+pub struct Tablebase {
+    max_pieces: usize,
+    tables: HashMap<Material, Vec<u8>>,
+}
+
+const MAGIC: &[u8; 4] = b"CKTB";
+const VERSION: u8 = 1;
+
+impl Tablebase {
+    pub fn generate(max_pieces: usize) -> Self {
+        let mut tablebase = Self {
+            max_pieces,
+            tables: HashMap::new(),
+        };
+        for material in Material::all(max_pieces) {
+            let table = tablebase.solve(material);
+            tablebase.tables.insert(material, table);
+        }
+        tablebase
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // the value of a position after a complete turn, from the perspective of the player to move in it
+    // moves that change the material lead to tables that are already solved
+    fn lookup(&self, state: &fast::BoardState) -> Option<Value> {
+        if let GameOutcome::Win(winner) = Checkers::get_outcome(state) {
+            return Some(if winner == state.get_turn() { Value::Win(0) } else { Value::Loss(0) });
+        }
+        let material = Material::of(state);
+        let table = self.tables.get(&material)?;
+        Some(Value::from_byte(table[material.index(state)]))
+    }
+
+    fn solve(&self, material: Material) -> Vec<u8> {
+        enum Successor {
+            // a position in the table being solved
+            Unsolved(usize),
+            // a position with a different material (or the end of the game)
+            Solved(Value),
+        }
+
+        let size = material.size();
+        let mut values = vec![None; size];
+        let mut successors = Vec::with_capacity(size);
+        let mut longest_solved = 0;
+
+        for (index, value) in values.iter_mut().enumerate() {
+            let mut position_successors = vec![];
+            if let Some(state) = material.position(index) {
+                for mv in state.get_legal_compound_moves() {
+                    let next_state = state.make_compound_move(&mv);
+                    if Material::of(&next_state) == material && !next_state.is_game_over() {
+                        position_successors.push(Successor::Unsolved(material.index(&next_state)));
+                    } else {
+                        let value = self.lookup(&next_state).expect("smaller tables are solved first");
+                        if let Value::Win(distance) | Value::Loss(distance) = value {
+                            longest_solved = longest_solved.max(distance);
+                        }
+                        position_successors.push(Successor::Solved(value));
+                    }
+                }
+
+                // a player that can not move loses
+                if position_successors.is_empty() {
+                    *value = Some(Value::Loss(0));
+                }
+            } else {
+                *value = Some(Value::Draw);
+            }
+            successors.push(position_successors);
+        }
+
+        // in round `distance`, a position is won if a move leads to a loss in `distance - 1` for the opponent,
+        // and lost if all moves lead to wins for the opponent that take at most `distance - 1` turns
+        let mut distance = 1;
+        loop {
+            let mut changed = false;
+            for index in 0..size {
+                if values[index].is_some() {
+                    continue;
+                }
+
+                let next_values = successors[index].iter().map(|successor| match successor {
+                    Successor::Unsolved(next_index) => values[*next_index],
+                    Successor::Solved(value) => Some(*value),
+                }).collect::<Vec<_>>();
+
+                if next_values.contains(&Some(Value::Loss(distance - 1))) {
+                    values[index] = Some(Value::Win(distance));
+                    changed = true;
+                } else if next_values.iter().all(|value| matches!(value, Some(Value::Win(d)) if *d < distance)) {
+                    values[index] = Some(Value::Loss(distance));
+                    changed = true;
+                }
+            }
+
+            // once the other tables can not contribute anymore, a round without progress means nothing else will be
+            if !changed && distance > longest_solved {
+                break;
+            }
+            assert!(distance < MAX_DISTANCE, "distance does not fit in the table");
+            distance += 1;
+        }
+
+        // neither side can force anything from the remaining positions
+        values.into_iter().map(|value| value.unwrap_or(Value::Draw).to_byte()).collect()
+    }
+
+    // `None` for positions the tablebase does not cover (too many pieces, or in the middle of a capture chain)
+    pub fn probe<C: Checkers>(&self, state: C) -> Option<Value> {
+        let state = fast::BoardState::from(state.into());
+        if state.capturing() != 0 || (state.tiles_human() | state.tiles_cpu()).count_ones() as usize > self.max_pieces {
+            return None;
+        }
+        self.lookup(&state)
+    }
+
+    // the move that wins the fastest, loses the slowest or keeps the draw
+    // this also works in the middle of a capture chain, since the moves it compares are complete turns
+    pub fn best_move<C: Checkers>(&self, state: C) -> Option<CompoundMove> {
+        let state = fast::BoardState::from(state.into());
+        if (state.tiles_human() | state.tiles_cpu()).count_ones() as usize > self.max_pieces {
+            return None;
+        }
+
+        let mut best_move = None;
+        for mv in state.get_legal_compound_moves() {
+            let next_value = self.lookup(&state.make_compound_move(&mv))?.previous();
+            let is_better = match best_move {
+                None => true,
+                Some((_, best_value)) => Self::is_better(next_value, best_value),
+            };
+            if is_better {
+                best_move = Some((mv, next_value));
+            }
+        }
+
+        best_move.map(|(mv, _)| mv)
+    }
+
+    fn is_better(value: Value, other: Value) -> bool {
+        match (value, other) {
+            (Value::Win(distance), Value::Win(other_distance)) => distance < other_distance,
+            (Value::Loss(distance), Value::Loss(other_distance)) => distance > other_distance,
+            (Value::Win(_), _) => true,
+            (Value::Draw, Value::Loss(_)) => true,
+            _ => false,
+        }
+    }
+
+    // the on-disk format: a header (magic, version, piece count, table count), then every table as its material,
+    // its length and its bytes (lengths are little-endian u32s)
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, self.max_pieces as u8])?;
+        writer.write_all(&(self.tables.len() as u32).to_le_bytes())?;
+
+        let mut materials = self.tables.keys().collect::<Vec<_>>();
+        materials.sort();
+        for material in materials {
+            let table = &self.tables[material];
+            writer.write_all(&material.counts())?;
+            writer.write_all(&(table.len() as u32).to_le_bytes())?;
+            writer.write_all(table)?;
+        }
+        Ok(())
+    }
+
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        let mut header = [0; 10];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not a tablebase"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid("unsupported tablebase version"));
+        }
+        let max_pieces = header[5] as usize;
+        let table_count = u32::from_le_bytes(header[6..10].try_into().unwrap());
+
+        let mut tables = HashMap::new();
+        for _ in 0..table_count {
+            let mut table_header = [0; 8];
+            reader.read_exact(&mut table_header)?;
+            let material = Material {
+                human_men: table_header[0],
+                human_kings: table_header[1],
+                cpu_men: table_header[2],
+                cpu_kings: table_header[3],
+            };
+            let length = u32::from_le_bytes(table_header[4..8].try_into().unwrap()) as usize;
+            if material.pieces() > max_pieces || length != material.size() {
+                return Err(invalid("table does not match its material"));
+            }
+
+            let mut table = vec![0; length];
+            reader.read_exact(&mut table)?;
+            tables.insert(material, table);
+        }
+
+        Ok(Self { max_pieces, tables })
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Self::read(&mut io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::fen;
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(BINOMIAL[32][2], 496);
+        for (count, combinations) in BINOMIAL[32].iter().enumerate().take(4) {
+            for rank_ in 0..*combinations.min(&1000) {
+                let squares = unrank(rank_, count);
+                assert_eq!(squares.count_ones() as usize, count);
+                assert_eq!(rank(squares), rank_);
+            }
+        }
+        assert_eq!(to_squares(to_bits(0xdead_beef)), 0xdead_beef);
+    }
+
+    #[test]
+    fn test_index() {
+        let material = Material { human_men: 1, human_kings: 0, cpu_men: 0, cpu_kings: 1 };
+        for index in 0..material.size() {
+            if let Some(state) = material.position(index) {
+                assert_eq!(Material::of(&state), material);
+                assert_eq!(material.index(&state), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_values() {
+        let tablebase = Tablebase::generate(2);
+
+        // two kings can not force anything against each other
+        let state: fast::BoardState = fen::from_fen("B:WK1:BK32").unwrap();
+        assert_eq!(tablebase.probe(state), Some(Value::Draw));
+
+        // a capture right away
+        let state: fast::BoardState = fen::from_fen("B:W18:B14").unwrap();
+        assert_eq!(tablebase.probe(state), Some(Value::Win(1)));
+        assert_eq!(tablebase.best_move(state).unwrap().to_string(), "14x23");
+
+        // the king hunts down the man before it can be crowned
+        let state: fast::BoardState = fen::from_fen("B:WK28:B26").unwrap();
+        assert_eq!(tablebase.probe(state), Some(Value::Loss(12)));
+        let state = state.make_compound_move(&tablebase.best_move(state).unwrap());
+        assert_eq!(tablebase.probe(state), Some(Value::Win(11)));
+
+        // too many pieces
+        assert_eq!(tablebase.probe(<fast::BoardState as Checkers>::default()), None);
+    }
+
+    // every won position has a move to a position that is lost in one turn less for the opponent
+    #[test]
+    fn test_best_moves() {
+        let tablebase = Tablebase::generate(2);
+        for material in Material::all(2) {
+            for index in 0..material.size() {
+                let Some(state) = material.position(index) else { continue };
+                let value = tablebase.probe(state).unwrap();
+                let Some(mv) = tablebase.best_move(state) else {
+                    assert_eq!(value, Value::Loss(0));
+                    continue;
+                };
+                assert_eq!(tablebase.lookup(&state.make_compound_move(&mv)).unwrap().previous(), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_read() {
+        let tablebase = Tablebase::generate(2);
+        let mut bytes = vec![];
+        tablebase.write(&mut bytes).unwrap();
+
+        let read = Tablebase::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read.max_pieces(), 2);
+        assert_eq!(read.tables, tablebase.tables);
+
+        bytes[0] = b'X';
+        assert!(Tablebase::read(&mut bytes.as_slice()).is_err());
+        assert!(Tablebase::read(&mut &bytes[..20]).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// zobrist keys: every (piece, square) combination gets a random 64-bit key, and a position's hash is the xor
+// of the keys of everything on the board, so a move only has to xor out what changed and xor in the new state
+// the keys are generated at compile time from a fixed seed, so hashes are stable across builds (and backends)
+use super::Player;
+use super::super::public;
+
+
+// splitmix64, see https://prng.di.unimi.it/splitmix64.c
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (state, z ^ (z >> 31))
+}
+
+// human men, human kings, cpu men, cpu kings (each for all 32 dark squares), the capturing piece and the turn
+const KEYS: [u64; 4 * 32 + 32 + 1] = {
+    let mut keys = [0; 4 * 32 + 32 + 1];
+    let mut state = 0x636b7273; // "ckrs"
+    let mut i = 0;
+    while i < keys.len() {
+        let (next_state, key) = splitmix64(state);
+        keys[i] = key;
+        state = next_state;
+        i += 1;
+    }
+    keys
+};
+
+// the dark squares, numbered row by row starting from the top left
+pub const fn square_index(coords: (u8, u8)) -> usize {
+    debug_assert!(coords.0 < 8 && coords.1 < 8 && (coords.0 + coords.1) % 2 == 1, "not a dark square");
+    4 * coords.0 as usize + coords.1 as usize / 2
+}
+
+pub const fn piece_key(coords: (u8, u8), player: Player, is_king: bool) -> u64 {
+    let kind = match (player, is_king) {
+        (Player::Human, false) => 0,
+        (Player::Human, true) => 1,
+        (Player::Cpu, false) => 2,
+        (Player::Cpu, true) => 3,
+    };
+    KEYS[32 * kind + square_index(coords)]
+}
+
+pub const fn capturing_key(coords: (u8, u8)) -> u64 {
+    KEYS[4 * 32 + square_index(coords)]
+}
+
+// xored in whenever the cpu is to move
+pub const TURN_KEY: u64 = KEYS[4 * 32 + 32];
+
+pub const fn turn_key(turn: Player) -> u64 {
+    match turn {
+        Player::Human => 0,
+        Player::Cpu => TURN_KEY,
+    }
+}
+
+// the hash of a position computed from scratch, the boards use this to initialize their keys
+pub fn hash_position(state: &public::BoardState) -> u64 {
+    let mut hash = turn_key(state.turn);
+    for (row, tiles) in state.tiles.iter().enumerate() {
+        for (col, tile) in tiles.iter().enumerate() {
+            if let Some(tile) = tile {
+                hash ^= piece_key((row as u8, col as u8), tile.player, tile.is_king);
+            }
+        }
+    }
+    if let Some(coords) = state.capturing {
+        hash ^= capturing_key(coords);
+    }
+    hash
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{fast, slow, Checkers};
+    use rand::prelude::*;
+    use rand::rngs::SmallRng;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut keys = KEYS.to_vec();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), KEYS.len());
+    }
+
+    // the incrementally updated hash has to match the one computed from scratch, on both backends
+    fn check_incremental_hash<C: Checkers>() -> Vec<u64> {
+        let mut random = SmallRng::seed_from_u64(11);
+        let mut hashes = vec![];
+
+        for _ in 0..50 {
+            let mut state = C::default();
+            // random games between kings can go on forever
+            for _ in 0..200 {
+                if state.is_game_over() {
+                    break;
+                }
+                let public_state: public::BoardState = state.into();
+                assert_eq!(state.zobrist_hash(), hash_position(&public_state));
+                assert_eq!(C::from(public_state).zobrist_hash(), state.zobrist_hash());
+                hashes.push(state.zobrist_hash());
+
+                // the backends list moves in different orders
+                let mut moves = state.get_legal_moves();
+                moves.sort_by_key(|mv| (mv.from, mv.to));
+                state = state.make_move(*moves.choose(&mut random).unwrap());
+            }
+        }
+
+        hashes
+    }
+
+    #[test]
+    fn test_incremental_hash() {
+        assert_eq!(check_incremental_hash::<slow::BoardState>(), check_incremental_hash::<fast::BoardState>());
+    }
+
+    #[test]
+    fn test_turn_changes_hash() {
+        let state = <fast::BoardState as Checkers>::default();
+        let mut public_state: public::BoardState = state.into();
+        public_state.turn = Player::Cpu;
+        assert_eq!(hash_position(&public_state), state.zobrist_hash() ^ TURN_KEY);
+    }
+}
@@ -1,13 +1,39 @@
-use crate::agent::Agent;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+use crate::agent::{Agent, AgentKind, MctsConfig};
+use crate::agent::book::{self, OpeningBook};
+use crate::agent::mcts_hash::MctsHashAgent;
 use crate::board::Checkers;
+use crate::board::history::GameHistory;
+use crate::board::notation;
+use crate::board::tablebase::{self, Tablebase, Value};
+use crate::pdn;
+use crate::error::EngineError;
 use crate::public;
 use crate::board;
 use crate::agent;
 
+// the board backend used by all commands, any `Checkers` implementation can be dropped in here
+type Board = board::fast::BoardState;
 
+fn to_board(state: public::BoardState) -> Result<Board, EngineError> {
+    state.validate()?;
+    Ok(state.into())
+}
+
+fn ensure_ongoing(state: &Board) -> Result<(), EngineError> {
+    match Checkers::get_outcome(state) {
+        board::GameOutcome::Ongoing => Ok(()),
+        outcome => Err(EngineError::GameOver(outcome)),
+    }
+}
+
+
+// a finished game (including a blocked player) has no legal moves, so the outcome is reported instead
 #[tauri::command]
-pub async fn get_legal_moves(state: public::BoardState) -> Result<Vec<board::Move>, ()> {
-    let state: board::slow::BoardState = state.into();
+pub async fn get_legal_moves(state: public::BoardState) -> Result<Vec<board::Move>, EngineError> {
+    let state = to_board(state)?;
+    ensure_ongoing(&state)?;
     let moves = state.get_legal_moves();
     let result = moves.iter().map(|m| (*m).into()).collect();
 
@@ -15,24 +41,185 @@ pub async fn get_legal_moves(state: public::BoardState) -> Result<Vec<board::Mov
 }
 
 #[tauri::command]
-pub async fn make_move(state: public::BoardState, mv: board::Move) -> Result<public::BoardState, ()> {
-    let mut state: board::slow::BoardState = state.into();
-    state = state.make_move(mv.into());
-    Ok(state.into())
+pub async fn make_move(state: public::BoardState, mv: board::Move) -> Result<public::BoardState, EngineError> {
+    let state = to_board(state)?;
+    Ok(state.try_make_move(mv)?.into())
+}
+
+#[tauri::command]
+pub async fn get_legal_compound_moves(state: public::BoardState) -> Result<Vec<board::CompoundMove>, EngineError> {
+    let state = to_board(state)?;
+    ensure_ongoing(&state)?;
+    Ok(state.get_legal_compound_moves())
+}
+
+#[tauri::command]
+pub async fn make_compound_move(state: public::BoardState, mv: board::CompoundMove) -> Result<public::BoardState, EngineError> {
+    let state = to_board(state)?;
+    Ok(state.try_make_compound_move(&mv)?.into())
 }
 
 #[tauri::command]
-pub async fn get_default_state() -> Result<public::BoardState, ()> {
-    let state = board::slow::BoardState::default();
+pub async fn get_default_state() -> Result<public::BoardState, EngineError> {
+    let state = <Board as Checkers>::default();
     Ok(state.into())
 }
 
+// the opening book consulted by `get_best_move`, empty until one is loaded
+pub type Book = RwLock<OpeningBook>;
+
+// the monte carlo agent, which keeps its statistics from one move to the next
+pub type Mcts = Mutex<MctsHashAgent<Board>>;
+
+// the endgame tablebase, empty until `prepare_tablebase` has loaded or generated it
+static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+
+// generating the tablebase takes a while, so it happens in the background and `get_best_move` searches until it is
+// ready. it is saved to `dir`, so later runs only have to load it
+pub fn prepare_tablebase(dir: Option<PathBuf>) {
+    std::thread::spawn(move || {
+        let path = dir.map(|dir| dir.join("tablebase.cktb"));
+        let saved = path.as_ref()
+            .and_then(|path| Tablebase::load(path).ok())
+            .filter(|tablebase| tablebase.max_pieces() >= tablebase::DEFAULT_MAX_PIECES);
+        let tablebase = saved.unwrap_or_else(|| {
+            let tablebase = Tablebase::generate(tablebase::DEFAULT_MAX_PIECES);
+            if let Some(path) = &path {
+                // without a saved copy, it is simply generated again next time
+                let _ = std::fs::create_dir_all(path.parent().unwrap()).and_then(|_| tablebase.save(path));
+            }
+            tablebase
+        });
+        let _ = TABLEBASE.set(tablebase);
+    });
+}
+
 #[tauri::command]
-pub async fn get_best_move(state: public::BoardState) -> Result<board::Move, ()> {
-    let mut agent = agent::mcts_hash::MctsHashAgent::default();
-    let state: board::slow::BoardState = state.into();
-    println!("Getting best move");
-    let mv = agent.get_best_move(state);
-    println!("Got best move {:?}", mv);
-    Ok(mv.into())
-}
\ No newline at end of file
+pub async fn get_best_move(
+    book: tauri::State<'_, Book>,
+    mcts: tauri::State<'_, Mcts>,
+    state: public::BoardState,
+    agent: Option<AgentKind>,
+    config: Option<MctsConfig>,
+) -> Result<board::Move, EngineError> {
+    let state = to_board(state)?;
+    ensure_ongoing(&state)?;
+
+    if let Some(mv) = book.read().unwrap().pick(state, &mut rand::thread_rng()) {
+        return Ok(mv);
+    }
+
+    // decided endgames are looked up instead of searched, drawn ones are left to the agent, which at least keeps
+    // trying to make something of them
+    if let Some(tablebase) = TABLEBASE.get() {
+        if matches!(tablebase.probe(state), Some(Value::Win(_) | Value::Loss(_))) {
+            if let Some(mv) = tablebase.best_move(state) {
+                return Ok(mv.steps().next().unwrap());
+            }
+        }
+    }
+
+    // a bug in the agent should not take the whole command (and the frontend waiting for it) down with it
+    let mv = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match agent.unwrap_or_default() {
+        AgentKind::Mcts => {
+            // all cores are used unless the config says otherwise
+            let mut config = config.unwrap_or_default();
+            config.threads.get_or_insert_with(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+            // a search that panicked may have left the statistics half updated, so they are started over
+            let mut agent = mcts.lock().unwrap_or_else(|poisoned| {
+                mcts.clear_poison();
+                let mut agent = poisoned.into_inner();
+                *agent = MctsHashAgent::default();
+                agent
+            });
+            agent.config = config;
+            agent.get_best_move(state)
+        }
+        AgentKind::AlphaBeta => agent::alphabeta::AlphaBetaAgent::default().get_best_move(state),
+    }))
+        .map_err(|panic| EngineError::SearchFailed(panic_message(panic)))?;
+    Ok(mv)
+}
+
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "the agent panicked".to_string()
+    }
+}
+
+// the frontend only ever sends single positions, so draws have to be detected by replaying the game so far
+#[tauri::command]
+pub async fn get_outcome(start: public::BoardState, moves: Vec<board::Move>) -> Result<board::GameOutcome, EngineError> {
+    let mut history = GameHistory::new(to_board(start)?);
+    for mv in moves {
+        history.try_make_move(mv)?;
+    }
+    Ok(history.get_outcome())
+}
+
+#[tauri::command]
+pub async fn parse_move(notation: String) -> Result<board::CompoundMove, EngineError> {
+    Ok(notation.parse()?)
+}
+
+#[tauri::command]
+pub async fn format_moves(moves: Vec<board::Move>) -> Result<Vec<String>, EngineError> {
+    Ok(notation::format_moves(&moves))
+}
+
+#[tauri::command]
+pub async fn load_position(fen: String) -> Result<public::BoardState, EngineError> {
+    let state: public::BoardState = fen.parse()?;
+    state.validate()?;
+    Ok(state)
+}
+
+#[tauri::command]
+pub async fn export_position(state: public::BoardState) -> Result<String, EngineError> {
+    state.validate()?;
+    Ok(state.to_fen())
+}
+
+// the game is replayed to fill in the result, and multi-jumps are merged into single moves
+#[tauri::command]
+pub async fn export_game(start: public::BoardState, moves: Vec<board::Move>) -> Result<String, EngineError> {
+    let start = to_board(start)?;
+    let mut history = GameHistory::new(start);
+    for mv in &moves {
+        history.try_make_move(*mv)?;
+    }
+    let result = match history.get_outcome() {
+        board::GameOutcome::Win(player) => pdn::PdnResult::Win(player),
+        board::GameOutcome::Draw(_) => pdn::PdnResult::Draw,
+        board::GameOutcome::Ongoing => pdn::PdnResult::Unknown,
+    };
+
+    let mut game = pdn::Game::new(start, &notation::merge_steps(&moves), result);
+    game.set_tag("Event", "chkrs");
+    game.set_tag("Black", "Human");
+    game.set_tag("White", "CPU");
+    Ok(game.write())
+}
+
+// only the first game of the file is loaded, its moves are split into single steps like the ones the board plays
+#[tauri::command]
+pub async fn import_game(pdn: String) -> Result<(public::BoardState, Vec<board::Move>), EngineError> {
+    let games = pdn::read(&pdn)?;
+    let game = games.first().ok_or_else(|| EngineError::InvalidNotation("no game found".to_string()))?;
+    let start = game.start::<Board>()?;
+    let (moves, _) = game.replay::<Board>()?;
+    Ok((start.into(), moves.iter().flat_map(|mv| mv.steps()).collect()))
+}
+
+// replaces the opening book with one built from the games, returns the number of positions in it
+#[tauri::command]
+pub async fn load_book(book: tauri::State<'_, Book>, pdn: String) -> Result<usize, EngineError> {
+    let new_book = OpeningBook::from_pdn(&pdn, book::DEFAULT_MAX_PLIES)?;
+    let positions = new_book.len();
+    *book.write().unwrap() = new_book;
+    Ok(positions)
+}
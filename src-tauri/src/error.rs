@@ -0,0 +1,61 @@
+use ts_rs::TS;
+use crate::board::{GameOutcome, IllegalMove};
+use crate::board::fen::FenError;
+use crate::board::notation::NotationError;
+use crate::pdn::PdnError;
+
+
+// everything that can go wrong in a command, serialized as-is so the frontend can tell the cases apart
+#[derive(TS, Debug, PartialEq, Eq, Clone, serde::Deserialize, serde::Serialize)]
+#[ts(export)]
+pub enum EngineError {
+    // the board sent by the frontend does not describe a reachable position
+    InvalidBoard(String),
+    IllegalMove(IllegalMove),
+    InvalidNotation(String),
+    // there is nothing to do (e.g. no legal moves) because the game has already ended
+    GameOver(GameOutcome),
+    // the agent failed (or panicked) while searching for a move
+    SearchFailed(String),
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::InvalidBoard(reason) => write!(f, "invalid board: {}", reason),
+            EngineError::IllegalMove(illegal_move) => write!(f, "illegal move: {}", illegal_move),
+            EngineError::InvalidNotation(reason) => write!(f, "invalid notation: {}", reason),
+            EngineError::GameOver(outcome) => write!(f, "the game is over ({:?})", outcome),
+            EngineError::SearchFailed(reason) => write!(f, "search failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<IllegalMove> for EngineError {
+    fn from(value: IllegalMove) -> Self {
+        EngineError::IllegalMove(value)
+    }
+}
+
+impl From<NotationError> for EngineError {
+    fn from(value: NotationError) -> Self {
+        EngineError::InvalidNotation(value.to_string())
+    }
+}
+
+impl From<FenError> for EngineError {
+    fn from(value: FenError) -> Self {
+        EngineError::InvalidNotation(value.to_string())
+    }
+}
+
+impl From<PdnError> for EngineError {
+    fn from(value: PdnError) -> Self {
+        match value {
+            PdnError::InvalidBoard(reason) => EngineError::InvalidBoard(reason),
+            _ => EngineError::InvalidNotation(value.to_string()),
+        }
+    }
+}
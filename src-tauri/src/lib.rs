@@ -1,2 +1,5 @@
 pub mod board;
-pub mod public;
\ No newline at end of file
+pub mod agent;
+pub mod public;
+pub mod error;
+pub mod pdn;
\ No newline at end of file
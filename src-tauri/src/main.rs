@@ -6,14 +6,27 @@ mod board;
 mod agent;
 mod public;
 mod commands;
+mod error;
+mod pdn;
 
 
 
 fn main() {
   tauri::Builder::default()
+    .manage(commands::Book::default())
+    .manage(commands::Mcts::default())
+    .setup(|app| {
+      commands::prepare_tablebase(app.path_resolver().app_data_dir());
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
       commands::get_legal_moves, commands::make_move,
-      commands::get_default_state, commands::get_best_move
+      commands::get_default_state, commands::get_best_move,
+      commands::get_outcome, commands::get_legal_compound_moves,
+      commands::make_compound_move, commands::parse_move,
+      commands::format_moves, commands::load_position,
+      commands::export_position, commands::export_game,
+      commands::import_game, commands::load_book
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
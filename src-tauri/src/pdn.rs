@@ -0,0 +1,485 @@
+// reading and writing of complete games in the Portable Draughts Notation, e.g.
+//
+//   [Event "Casual game"]
+//   [Black "Human"]
+//   [White "CPU"]
+//   [Result "1-0"]
+//   1. 11-15 23-19 2. 8-11 {a comment} 22-17 3. 15x24 ... 1-0
+//
+// moves are written in the standard 1-32 notation (see `board::notation`), where black is the human
+// results follow the chess convention, i.e. "1-0" means that white (the cpu) won
+use std::fmt;
+use crate::board::{fast, Checkers, CompoundMove, Player};
+use crate::board::fen::{self, FenError};
+use crate::board::notation;
+use crate::error::EngineError;
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PdnError {
+    // an opening bracket/brace/parenthesis without its closing counterpart
+    Unterminated(char),
+    // a tag that is not of the form [Key "Value"]
+    InvalidTag(String),
+    // a token in the move text that is neither a move, a move number nor a result
+    InvalidToken(String),
+    InvalidFen(FenError),
+    // a [FEN] start position that no game can reach (see `public::BoardState::validate`)
+    InvalidBoard(String),
+    // a move that is not legal in the position it is played in, `ply` counts from 1
+    IllegalMove { ply: usize, notation: String },
+    // a shortened capture (e.g. "22x8") that matches several different capture chains
+    AmbiguousMove { ply: usize, notation: String },
+}
+
+impl fmt::Display for PdnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdnError::Unterminated(c) => write!(f, "unterminated '{}'", c),
+            PdnError::InvalidTag(tag) => write!(f, "invalid tag '{}'", tag),
+            PdnError::InvalidToken(token) => write!(f, "unexpected '{}' in move text", token),
+            PdnError::InvalidFen(error) => write!(f, "{}", error),
+            PdnError::InvalidBoard(reason) => write!(f, "invalid start position: {}", reason),
+            PdnError::IllegalMove { ply, notation } => write!(f, "illegal move '{}' at ply {}", notation, ply),
+            PdnError::AmbiguousMove { ply, notation } => write!(f, "ambiguous move '{}' at ply {}", notation, ply),
+        }
+    }
+}
+
+impl std::error::Error for PdnError {}
+
+impl From<FenError> for PdnError {
+    fn from(value: FenError) -> Self {
+        PdnError::InvalidFen(value)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PdnResult {
+    Win(Player),
+    Draw,
+    #[default]
+    Unknown,
+}
+
+impl PdnResult {
+    fn parse(token: &str) -> Option<Self> {
+        match token {
+            "1-0" | "2-0" => Some(PdnResult::Win(Player::Cpu)),
+            "0-1" | "0-2" => Some(PdnResult::Win(Player::Human)),
+            "1/2-1/2" | "1-1" => Some(PdnResult::Draw),
+            "*" => Some(PdnResult::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PdnResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdnResult::Win(Player::Cpu) => write!(f, "1-0"),
+            PdnResult::Win(Player::Human) => write!(f, "0-1"),
+            PdnResult::Draw => write!(f, "1/2-1/2"),
+            PdnResult::Unknown => write!(f, "*"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GameMove {
+    // kept as written, since published games often shorten captures to just the first and last square
+    pub notation: String,
+    // the comment following the move, if any
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Game {
+    // in the order they appear in the file
+    pub tags: Vec<(String, String)>,
+    // the comment before the first move, if any
+    pub comment: Option<String>,
+    pub moves: Vec<GameMove>,
+    pub result: PdnResult,
+}
+
+impl Game {
+    pub fn new<C: Checkers>(start: C, moves: &[CompoundMove], result: PdnResult) -> Self {
+        let mut game = Game {
+            tags: vec![],
+            comment: None,
+            moves: moves.iter().map(|mv| GameMove { notation: mv.to_string(), comment: None }).collect(),
+            result,
+        };
+
+        // the "seven tag roster", with PDN's placeholders for unknown values
+        for (key, value) in [("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"), ("Black", "?"), ("White", "?")] {
+            game.set_tag(key, value);
+        }
+        game.set_tag("Result", &result.to_string());
+
+        let fen = fen::board_to_fen(start);
+        if fen != fen::board_to_fen(C::default()) {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+
+        game
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, key: &str, value: &str) {
+        match self.tags.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.tags.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn start<C: Checkers>(&self) -> Result<C, PdnError> {
+        match self.tag("FEN") {
+            Some(fen) => {
+                let state = fen::parse_fen(fen)?;
+                match state.validate() {
+                    Err(EngineError::InvalidBoard(reason)) => Err(PdnError::InvalidBoard(reason)),
+                    _ => Ok(state.into()),
+                }
+            }
+            None => Ok(C::default()),
+        }
+    }
+
+    // plays through the game, checking every move and resolving shortened captures
+    // returns the fully specified moves along with the final position
+    pub fn replay<C: Checkers>(&self) -> Result<(Vec<CompoundMove>, C), PdnError> {
+        let mut state = self.start::<C>()?;
+        let mut moves = vec![];
+
+        for (i, game_move) in self.moves.iter().enumerate() {
+            let mv = resolve_move(&state, &game_move.notation, i + 1)?;
+            state = state.make_compound_move(&mv);
+            moves.push(mv);
+        }
+
+        Ok((moves, state))
+    }
+
+    pub fn write(&self) -> String {
+        let mut output = String::new();
+        for (key, value) in &self.tags {
+            output.push_str(&format!("[{} \"{}\"]\n", key, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        output.push('\n');
+
+        let mut tokens = vec![];
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+
+        // move numbers count full moves, starting with black (unless the position says otherwise)
+        let white_starts = self.tag("FEN").is_some_and(|fen| fen.trim_start().starts_with(['W', 'w']));
+        for (i, game_move) in self.moves.iter().enumerate() {
+            let ply = i + white_starts as usize;
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            } else if i == 0 {
+                tokens.push("1...".to_string());
+            }
+            tokens.push(game_move.notation.clone());
+            if let Some(comment) = &game_move.comment {
+                tokens.push(format!("{{{}}}", comment));
+            }
+        }
+        tokens.push(self.result.to_string());
+
+        // wrap the move text at 80 columns
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > 80 {
+                output.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                output.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            output.push_str(&token);
+        }
+        output.push('\n');
+
+        output
+    }
+}
+
+fn resolve_move<C: Checkers>(state: &C, notation: &str, ply: usize) -> Result<CompoundMove, PdnError> {
+    let illegal = || PdnError::IllegalMove { ply, notation: notation.to_string() };
+    let squares = notation::parse_squares(notation).map_err(|_| illegal())?;
+
+    // the written squares have to appear along the path in the same order, starting and ending at the same square
+    let candidates = state.get_legal_compound_moves().into_iter().filter(|mv| {
+        let mut path = std::iter::once(mv.from).chain(mv.path.iter().copied());
+        mv.from == squares[0] && mv.to() == *squares.last().unwrap() && squares.iter().all(|square| path.any(|s| s == *square))
+    }).collect::<Vec<_>>();
+
+    match candidates.as_slice() {
+        [] => Err(illegal()),
+        [mv] => Ok(mv.clone()),
+        // capturing the same pieces in a different order is still the same move
+        [first, rest @ ..] if rest.iter().all(|mv| state.make_compound_move(mv) == state.make_compound_move(first)) => Ok(first.clone()),
+        _ => Err(PdnError::AmbiguousMove { ply, notation: notation.to_string() }),
+    }
+}
+
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Move(String),
+    Result(PdnResult),
+}
+
+fn read_until(chars: &mut std::iter::Peekable<std::str::Chars>, open: char, close: char) -> Result<String, PdnError> {
+    let mut content = String::new();
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        match c {
+            '\\' if open == '[' && !escaped => escaped = true,
+            c if c == close && !escaped => return Ok(content),
+            c => {
+                if escaped && c != '"' && c != '\\' {
+                    content.push('\\');
+                }
+                escaped = false;
+                content.push(c);
+            },
+        }
+    }
+    Err(PdnError::Unterminated(open))
+}
+
+fn parse_tag(tag: &str) -> Result<Token, PdnError> {
+    let invalid = || PdnError::InvalidTag(tag.to_string());
+    let (key, value) = tag.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let value = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"')).ok_or_else(invalid)?;
+    Ok(Token::Tag(key.to_string(), value.to_string()))
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PdnError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '[' => tokens.push(parse_tag(&read_until(&mut chars, '[', ']')?)?),
+            '{' => tokens.push(Token::Comment(read_until(&mut chars, '{', '}')?.trim().to_string())),
+            // rest-of-line comments
+            ';' => {
+                let comment = chars.by_ref().take_while(|c| *c != '\n').collect::<String>();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            },
+            // variations are skipped entirely
+            '(' => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some(_) => (),
+                        None => return Err(PdnError::Unterminated('(')),
+                    }
+                }
+            },
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[{(;".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.extend(parse_word(&word)?);
+            },
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_word(word: &str) -> Result<Option<Token>, PdnError> {
+    if let Some(result) = PdnResult::parse(word) {
+        return Ok(Some(Token::Result(result)));
+    }
+    // numeric annotation glyphs
+    if word.starts_with('$') {
+        return Ok(None);
+    }
+
+    // move numbers ("12." or "12..."), possibly glued to the move that follows ("12.11-15")
+    let word = match word.rfind('.') {
+        Some(i) if word[..i].trim_end_matches('.').chars().all(|c| c.is_ascii_digit()) => &word[i + 1..],
+        _ => word,
+    };
+    if word.is_empty() {
+        return Ok(None);
+    }
+
+    // annotations like "11-15!" or "22x15?!"
+    let notation = word.trim_end_matches(['!', '?']);
+    match notation::parse_squares(notation) {
+        Ok(_) => Ok(Some(Token::Move(notation.to_string()))),
+        Err(_) => Err(PdnError::InvalidToken(word.to_string())),
+    }
+}
+
+pub fn read(text: &str) -> Result<Vec<Game>, PdnError> {
+    let mut games = vec![];
+    let mut game = Game::default();
+    let mut has_moves = false;
+
+    for token in tokenize(text)? {
+        match token {
+            Token::Tag(key, value) => {
+                // tags after the move text belong to the next game (in case the result was left out)
+                if has_moves {
+                    games.push(std::mem::take(&mut game));
+                    has_moves = false;
+                }
+                if key == "Result" {
+                    game.result = PdnResult::parse(&value).unwrap_or_default();
+                }
+                game.set_tag(&key, &value);
+            },
+            Token::Comment(comment) => match game.moves.last_mut() {
+                Some(last) => last.comment = Some(comment),
+                None => game.comment = Some(comment),
+            },
+            Token::Move(notation) => {
+                game.moves.push(GameMove { notation, comment: None });
+                has_moves = true;
+            },
+            Token::Result(result) => {
+                game.result = result;
+                games.push(std::mem::take(&mut game));
+                has_moves = false;
+            },
+        }
+    }
+
+    if has_moves || !game.tags.is_empty() {
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+// replays every game on the bitboard backend, so files can be rejected as a whole
+pub fn read_validated(text: &str) -> Result<Vec<Game>, PdnError> {
+    let games = read(text)?;
+    for game in &games {
+        game.replay::<fast::BoardState>()?;
+    }
+    Ok(games)
+}
+
+pub fn write(games: &[Game]) -> String {
+    games.iter().map(|game| game.write()).collect::<Vec<_>>().join("\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{slow, Move};
+
+    const GAME: &str = r#"
+[Event "Test \"quoted\""]
+[Black "Human"]
+[White "CPU"]
+[Result "0-1"]
+
+{opening} 1. 11-15 23-19 2. 8-11 22-17 {the cpu follows up} 3. 9-13 17-14 4. 10x17 21x14
+0-1
+
+[Event "Second"]
+[FEN "W:W18,K30:B14,6"]
+1... 18x9x2 *
+"#;
+
+    #[test]
+    fn test_read() {
+        let games = read(GAME).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let game = &games[0];
+        assert_eq!(game.tag("Event"), Some("Test \"quoted\""));
+        assert_eq!(game.tag("Black"), Some("Human"));
+        assert_eq!(game.result, PdnResult::Win(Player::Human));
+        assert_eq!(game.comment, Some("opening".to_string()));
+        assert_eq!(game.moves.len(), 8);
+        assert_eq!(game.moves[3].comment, Some("the cpu follows up".to_string()));
+        assert_eq!(game.moves[6].notation, "10x17");
+
+        let (moves, state) = game.replay::<slow::BoardState>().unwrap();
+        assert_eq!(moves.len(), 8);
+        assert_eq!(state.get_turn(), Player::Human);
+
+        let game = &games[1];
+        assert_eq!(game.result, PdnResult::Unknown);
+        let (moves, state) = game.replay::<fast::BoardState>().unwrap();
+        assert_eq!(moves[0].captured.len(), 2);
+        assert_eq!(fen::board_to_fen(state), "B:WK2,K30:B");
+    }
+
+    #[test]
+    fn test_shortened_capture() {
+        let games = read("[FEN \"W:W18:B14,6\"]\n1... 18x2 *").unwrap();
+        let (moves, _) = games[0].replay::<fast::BoardState>().unwrap();
+        assert_eq!(moves[0].to_string(), "18x9x2");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let games = read(GAME).unwrap();
+        let written = write(&games);
+        assert_eq!(read(&written).unwrap(), games);
+    }
+
+    #[test]
+    fn test_new() {
+        let start = slow::BoardState::default();
+        let moves = [
+            CompoundMove::from(Move { from: (5, 2), to: (4, 3), is_skip_move: false }),
+            CompoundMove::from(Move { from: (2, 1), to: (3, 2), is_skip_move: false }),
+        ];
+        let game = Game::new(start, &moves, PdnResult::Unknown);
+        assert_eq!(game.tag("FEN"), None);
+        assert!(game.write().ends_with("\n1. 11-15 24-19 *\n"));
+        assert_eq!(game.replay::<slow::BoardState>().unwrap().0, moves);
+
+        let start = start.make_move(Move { from: (5, 2), to: (4, 3), is_skip_move: false });
+        let game = Game::new(start, &moves[1..], PdnResult::Unknown);
+        assert!(game.tag("FEN").is_some());
+        assert!(game.write().ends_with("\n1... 24-19 *\n"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(read("[Event \"x\"").unwrap_err(), PdnError::Unterminated('['));
+        assert_eq!(read("1. 11-15 {oops").unwrap_err(), PdnError::Unterminated('{'));
+        assert_eq!(read("[Event]").unwrap_err(), PdnError::InvalidTag("Event".to_string()));
+        assert_eq!(read("1. e4").unwrap_err(), PdnError::InvalidToken("e4".to_string()));
+
+        let games = read("1. 11-15 11-15 *").unwrap();
+        assert_eq!(
+            games[0].replay::<slow::BoardState>().unwrap_err(),
+            PdnError::IllegalMove { ply: 2, notation: "11-15".to_string() }
+        );
+        assert!(read_validated("1. 11-15 11-15 *").is_err());
+        assert!(read_validated("1. 11-15 (1. 9-13) 23-19 *").is_ok());
+
+        let games = read("[FEN \"B:W1:B32\"] *").unwrap();
+        assert_eq!(
+            games[0].start::<slow::BoardState>().unwrap_err(),
+            PdnError::InvalidBoard("uncrowned man on (0, 1)".to_string())
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use ts_rs::TS;
-use crate::board::{Player, Move};
+use crate::board::Player;
+use crate::error::EngineError;
 
 #[derive(TS, Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
 pub struct Tile {
@@ -12,5 +13,78 @@ pub struct Tile {
 #[ts(export)]
 pub struct BoardState {
     pub tiles: [[Option<Tile>; 8]; 8],
-    pub turn: Player
+    pub turn: Player,
+    // the piece that is in the middle of a capture chain, if any
+    pub capturing: Option<(u8, u8)>
+}
+
+impl BoardState {
+    // checks the things the board backends take for granted
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let mut piece_counts = [0; 2];
+
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                let Some(tile) = tile else { continue };
+
+                if (row + col) % 2 == 0 {
+                    return Err(EngineError::InvalidBoard(format!("piece on light square ({}, {})", row, col)));
+                }
+
+                let promotion_row = match tile.player {
+                    Player::Human => 0,
+                    Player::Cpu => 7,
+                };
+                if row == promotion_row && !tile.is_king {
+                    return Err(EngineError::InvalidBoard(format!("uncrowned man on ({}, {})", row, col)));
+                }
+
+                piece_counts[tile.player as usize] += 1;
+            }
+        }
+
+        if piece_counts.iter().any(|count| *count > 12) {
+            return Err(EngineError::InvalidBoard("more than 12 pieces for one player".to_string()));
+        }
+
+        if let Some((row, col)) = self.capturing {
+            match self.tiles.get(row as usize).and_then(|tiles| tiles.get(col as usize)) {
+                Some(Some(tile)) if tile.player == self.turn => (),
+                _ => return Err(EngineError::InvalidBoard(format!("no capturing piece on ({}, {})", row, col))),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{slow, Checkers};
+
+    #[test]
+    fn test_validate() {
+        let mut state: BoardState = slow::BoardState::default().into();
+        assert_eq!(state.validate(), Ok(()));
+
+        state.capturing = Some((4, 1));
+        assert!(state.validate().is_err());
+        state.capturing = None;
+
+        state.tiles[0][0] = Some(Tile { player: Player::Cpu, is_king: false });
+        assert!(state.validate().is_err());
+        state.tiles[0][0] = None;
+
+        state.tiles[7][0] = None;
+        state.tiles[0][1] = Some(Tile { player: Player::Human, is_king: false });
+        assert!(state.validate().is_err());
+        state.tiles[0][1] = Some(Tile { player: Player::Human, is_king: true });
+        assert_eq!(state.validate(), Ok(()));
+
+        // a thirteenth human piece
+        state.tiles[4][1] = Some(Tile { player: Player::Human, is_king: false });
+        assert!(state.validate().is_err());
+    }
 }
@@ -1,5 +1,12 @@
 import { invoke } from '@tauri-apps/api/tauri';
-import { BoardState, Move } from './types';
+import {
+  AgentKind,
+  BoardState,
+  CompoundMove,
+  GameOutcome,
+  MctsConfig,
+  Move,
+} from './types';
 
 export async function invokeGetDefaultState(): Promise<BoardState> {
   return invoke<BoardState>('get_default_state');
@@ -16,6 +23,64 @@ export async function invokeMakeMove(
   return invoke<BoardState>('make_move', { state, mv });
 }
 
-export async function invokeGetBestMove(state: BoardState): Promise<Move> {
-  return invoke<Move>('get_best_move', { state });
+export async function invokeGetLegalCompoundMoves(
+  state: BoardState,
+): Promise<CompoundMove[]> {
+  return invoke<CompoundMove[]>('get_legal_compound_moves', { state });
+}
+
+export async function invokeMakeCompoundMove(
+  state: BoardState,
+  mv: CompoundMove,
+): Promise<BoardState> {
+  return invoke<BoardState>('make_compound_move', { state, mv });
+}
+
+// fields missing from the config take their default value
+export async function invokeGetBestMove(
+  state: BoardState,
+  agent: AgentKind,
+  config?: Partial<MctsConfig>,
+): Promise<Move> {
+  return invoke<Move>('get_best_move', { state, agent, config });
+}
+
+export async function invokeGetOutcome(
+  start: BoardState,
+  moves: Move[],
+): Promise<GameOutcome> {
+  return invoke<GameOutcome>('get_outcome', { start, moves });
+}
+
+export async function invokeParseMove(notation: string): Promise<CompoundMove> {
+  return invoke<CompoundMove>('parse_move', { notation });
+}
+
+export async function invokeFormatMoves(moves: Move[]): Promise<string[]> {
+  return invoke<string[]>('format_moves', { moves });
+}
+
+export async function invokeLoadPosition(fen: string): Promise<BoardState> {
+  return invoke<BoardState>('load_position', { fen });
+}
+
+export async function invokeExportPosition(state: BoardState): Promise<string> {
+  return invoke<string>('export_position', { state });
+}
+
+export async function invokeExportGame(
+  start: BoardState,
+  moves: Move[],
+): Promise<string> {
+  return invoke<string>('export_game', { start, moves });
+}
+
+export async function invokeImportGame(
+  pdn: string,
+): Promise<[BoardState, Move[]]> {
+  return invoke<[BoardState, Move[]]>('import_game', { pdn });
+}
+
+export async function invokeLoadBook(pdn: string): Promise<number> {
+  return invoke<number>('load_book', { pdn });
 }
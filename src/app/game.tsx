@@ -1,11 +1,26 @@
 'use client';
 import {
+  invokeExportGame,
+  invokeExportPosition,
+  invokeFormatMoves,
   invokeGetBestMove,
   invokeGetDefaultState,
   invokeGetLegalMoves,
+  invokeGetOutcome,
+  invokeImportGame,
+  invokeLoadBook,
+  invokeLoadPosition,
   invokeMakeMove,
 } from './api';
-import { Player, BoardState, Move } from './types';
+import {
+  AgentKind,
+  Player,
+  BoardState,
+  EngineError,
+  GameOutcome,
+  IllegalMove,
+  Move,
+} from './types';
 import { Fragment, useState, useEffect } from 'react';
 
 type Index = number;
@@ -15,29 +30,148 @@ const convertIndexToCoords = (i: Index): Coords => [Math.floor(i / 8), i % 8];
 const convertCoordsToIndex = (coords: Coords): Index =>
   coords[0] * 8 + coords[1];
 
+const describeOutcome = (outcome: GameOutcome): string | null => {
+  if (outcome === 'Ongoing') return null;
+  if ('Win' in outcome) return `${outcome.Win} wins!`;
+  return outcome.Draw === 'Repetition'
+    ? 'Draw by repetition'
+    : 'Draw (no progress)';
+};
+
+const describeIllegalMove = (error: IllegalMove): string => {
+  if (typeof error !== 'string') return 'The game is already over';
+  switch (error) {
+    case 'NoPiece':
+      return 'There is no piece to move';
+    case 'WrongSide':
+      return "It is not that piece's turn";
+    case 'IllegalDestination':
+      return 'That piece can not move there';
+    case 'CaptureAvailable':
+      return 'A capture is mandatory';
+  }
+};
+
+const describeError = (error: EngineError): string => {
+  if ('IllegalMove' in error) return describeIllegalMove(error.IllegalMove);
+  if ('InvalidBoard' in error) return `Invalid board: ${error.InvalidBoard}`;
+  if ('InvalidNotation' in error) return error.InvalidNotation;
+  if ('SearchFailed' in error) return `The CPU failed: ${error.SearchFailed}`;
+  return 'The game is already over';
+};
+
 export function Game() {
   const [boardState, setBoardState] = useState<BoardState | null>(null);
   const [selectedIndex, setSelectedIndex] = useState<number | null>(null);
   const [possibleMoves, setPossibleMoves] = useState<Move[] | null>(null);
+  const [startState, setStartState] = useState<BoardState | null>(null);
+  const [playedMoves, setPlayedMoves] = useState<Move[]>([]);
+  const [moveList, setMoveList] = useState<string[]>([]);
+  const [outcome, setOutcome] = useState<GameOutcome>('Ongoing');
+  const [engineError, setEngineError] = useState<EngineError | null>(null);
+  const [fen, setFen] = useState('');
+  const [pdn, setPdn] = useState('');
+  const [bookPositions, setBookPositions] = useState<number | null>(null);
+  const [agent, setAgent] = useState<AgentKind>('Mcts');
 
   useEffect(() => {
     const initialize = async () => {
       const defaultState = await invokeGetDefaultState();
+      setStartState(defaultState);
       setBoardState(defaultState);
     };
     initialize().catch(console.error);
   }, []);
 
+  const applyMove = async (state: BoardState, move: Move) => {
+    if (startState === null) return;
+    const newBoardState = await invokeMakeMove(state, move).catch(
+      (error: EngineError) => {
+        setEngineError(error);
+        return null;
+      },
+    );
+    if (newBoardState === null) return;
+    setEngineError(null);
+    const newPlayedMoves = [...playedMoves, move];
+    setOutcome(await invokeGetOutcome(startState, newPlayedMoves));
+    setPlayedMoves(newPlayedMoves);
+    setMoveList(await invokeFormatMoves(newPlayedMoves));
+    setBoardState(newBoardState);
+  };
+
+  const loadPosition = async () => {
+    const state = await invokeLoadPosition(fen).catch((error: EngineError) => {
+      setEngineError(error);
+      return null;
+    });
+    if (state === null) return;
+    setEngineError(null);
+    setStartState(state);
+    setPlayedMoves([]);
+    setMoveList([]);
+    setPossibleMoves(null);
+    setOutcome(await invokeGetOutcome(state, []));
+    setBoardState(state);
+  };
+
+  const exportPosition = async () => {
+    if (boardState === null) return;
+    setFen(await invokeExportPosition(boardState));
+  };
+
+  const importGame = async () => {
+    const game = await invokeImportGame(pdn).catch((error: EngineError) => {
+      setEngineError(error);
+      return null;
+    });
+    if (game === null) return;
+    const [start, moves] = game;
+    let state: BoardState | null = start;
+    for (const move of moves) {
+      state = await invokeMakeMove(state, move).catch((error: EngineError) => {
+        setEngineError(error);
+        return null;
+      });
+      if (state === null) return;
+    }
+    setEngineError(null);
+    setStartState(start);
+    setPlayedMoves(moves);
+    setMoveList(await invokeFormatMoves(moves));
+    setPossibleMoves(null);
+    setOutcome(await invokeGetOutcome(start, moves));
+    setBoardState(state);
+  };
+
+  const loadBook = async () => {
+    const positions = await invokeLoadBook(pdn).catch(
+      (error: EngineError) => {
+        setEngineError(error);
+        return null;
+      },
+    );
+    if (positions === null) return;
+    setEngineError(null);
+    setBookPositions(positions);
+  };
+
+  const exportGame = async () => {
+    if (startState === null) return;
+    setPdn(await invokeExportGame(startState, playedMoves));
+  };
+
   useEffect(() => {
     const makeBestMove = async () => {
       if (boardState === null || boardState.turn !== 'Cpu') return;
+      if (outcome !== 'Ongoing') return;
 
-      const bestMove = await invokeGetBestMove(boardState);
-      const newBoardState = await invokeMakeMove(boardState, bestMove);
-      setBoardState(newBoardState);
+      const bestMove = await invokeGetBestMove(boardState, agent);
+      await applyMove(boardState, bestMove);
     };
 
-    makeBestMove().catch(console.error);
+    makeBestMove().catch(setEngineError);
+    // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [boardState]);
 
   if (boardState === null) {
@@ -47,9 +181,18 @@ export function Game() {
   const handleClick = async (player: Player, index: number) => {
     if (player === 'Cpu') return;
     if (player !== boardState.turn) return;
+    if (outcome !== 'Ongoing') return;
     setSelectedIndex(index);
 
-    const moves = (await invokeGetLegalMoves(boardState)).filter(
+    // the command rejects with the outcome if the player to move has lost
+    const legalMoves = await invokeGetLegalMoves(boardState).catch(
+      (error: EngineError) => {
+        if ('GameOver' in error) setOutcome(error.GameOver);
+        else setEngineError(error);
+        return [];
+      },
+    );
+    const moves = legalMoves.filter(
       (move) => convertCoordsToIndex(move.from) === index,
     );
     setPossibleMoves(moves);
@@ -57,9 +200,8 @@ export function Game() {
 
   const handleGhostClick = async (from: Coords, to: Coords) => {
     const move = { from, to, is_skip_move: Math.abs(from[0] - to[0]) === 2 };
-    const newBoardState = await invokeMakeMove(boardState, move);
     setPossibleMoves(null);
-    setBoardState(newBoardState);
+    await applyMove(boardState, move);
   };
 
   function renderTileContents(index: Index) {
@@ -114,13 +256,73 @@ export function Game() {
         })}
       </div>
       <span className="mt-3 text-xl">
-        <span
-          className={` ${boardState.turn === 'Human' ? 'text-blue-500' : 'text-red-500'}`}
-        >
-          {boardState.turn}
-        </span>
-        &apos;s turn
+        {describeOutcome(outcome) ?? (
+          <Fragment>
+            <span
+              className={` ${boardState.turn === 'Human' ? 'text-blue-500' : 'text-red-500'}`}
+            >
+              {boardState.turn}
+            </span>
+            &apos;s turn
+          </Fragment>
+        )}
       </span>
+      {engineError !== null && (
+        <span className="mt-1 text-red-700">{describeError(engineError)}</span>
+      )}
+      <div className="mt-3 flex gap-2">
+        <input
+          className="w-96 border border-black px-1 font-mono"
+          placeholder="B:W21-32:B1-12"
+          value={fen}
+          onChange={(event) => setFen(event.target.value)}
+        />
+        <button className="border border-black px-2" onClick={loadPosition}>
+          Load
+        </button>
+        <button className="border border-black px-2" onClick={exportPosition}>
+          Export
+        </button>
+        <select
+          className="border border-black px-1"
+          value={agent}
+          onChange={(event) => setAgent(event.target.value as AgentKind)}
+        >
+          <option value="Mcts">MCTS</option>
+          <option value="AlphaBeta">Alpha-beta</option>
+        </select>
+      </div>
+      <div className="mt-3 flex gap-2">
+        <textarea
+          className="h-32 w-96 border border-black px-1 font-mono"
+          placeholder="[Event &quot;...&quot;] 1. 11-15 23-19 *"
+          value={pdn}
+          onChange={(event) => setPdn(event.target.value)}
+        />
+        <div className="flex flex-col gap-2">
+          <button className="border border-black px-2" onClick={importGame}>
+            Import game
+          </button>
+          <button className="border border-black px-2" onClick={exportGame}>
+            Export game
+          </button>
+          <button className="border border-black px-2" onClick={loadBook}>
+            Use as book
+          </button>
+          {bookPositions !== null && (
+            <span>{bookPositions} book positions</span>
+          )}
+        </div>
+      </div>
+      <ol className="mt-3 list-decimal font-mono">
+        {moveList
+          .filter((_, i) => i % 2 === 0)
+          .map((move, i) => (
+            <li key={i}>
+              {move} {moveList[2 * i + 1] ?? ''}
+            </li>
+          ))}
+      </ol>
     </Fragment>
   );
 }
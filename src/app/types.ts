@@ -1,5 +1,24 @@
+import { AgentKind } from '../../src-tauri/bindings/AgentKind';
 import { BoardState } from '../../src-tauri/bindings/BoardState';
+import { CompoundMove } from '../../src-tauri/bindings/CompoundMove';
+import { EngineError } from '../../src-tauri/bindings/EngineError';
+import { GameOutcome } from '../../src-tauri/bindings/GameOutcome';
+import { IllegalMove } from '../../src-tauri/bindings/IllegalMove';
+import { MctsConfig } from '../../src-tauri/bindings/MctsConfig';
 import { Move } from '../../src-tauri/bindings/Move';
 import { Player } from '../../src-tauri/bindings/Player';
+import { RolloutPolicy } from '../../src-tauri/bindings/RolloutPolicy';
 import { Tile } from '../../src-tauri/bindings/Tile';
-export type { BoardState, Player, Move, Tile };
+export type {
+  AgentKind,
+  BoardState,
+  CompoundMove,
+  EngineError,
+  GameOutcome,
+  IllegalMove,
+  MctsConfig,
+  Player,
+  Move,
+  RolloutPolicy,
+  Tile,
+};